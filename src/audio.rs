@@ -0,0 +1,296 @@
+mod player;
+
+use crate::minefield::{ActionOutcome, Mark, Object, Terminal};
+
+pub use player::PlayerBackend;
+
+/// Step by which the master volume goes up or down.
+pub const VOLUME_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// A single tile uncovered.
+    Reveal,
+    /// A blank tile uncovered, and the region it floods into.
+    Flood,
+    /// Tiles uncovered by exploring around a hint.
+    Chord,
+    Flag,
+    Unflag,
+    /// A mine going off, once for every ring of explosions.
+    Explosion,
+    Victory,
+}
+
+/// Whatever actually makes the sounds heard.
+pub trait Backend {
+    /// Start playing a sound, at a volume from 0 to 1.
+    fn play(&mut self, sound: Sound, volume: f32);
+}
+
+/// A backend that plays nothing, for headless runs and machines without a sound device.
+#[derive(Debug, Default)]
+pub struct NullBackend;
+
+/// The backend to play sounds through on this machine,
+/// [NullBackend] when no way of playing them was found.
+pub fn default_backend() -> Box<dyn Backend> {
+    match PlayerBackend::detect() {
+        Some(player) => Box::new(player),
+        None => Box::new(NullBackend),
+    }
+}
+
+/// The game's sound effects, played through a [Backend].
+pub struct Audio {
+    backend: Box<dyn Backend>,
+    /// Master volume, from 0 to 1.
+    volume: f32,
+    muted: bool,
+    clock_milisec: u32,
+    /// Sounds waiting for the clock to reach the time they are due at.
+    scheduled: Vec<(Sound, u32)>,
+}
+
+impl Backend for NullBackend {
+    fn play(&mut self, _sound: Sound, _volume: f32) {}
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Self {
+            backend,
+            volume: 0.8,
+            muted: false,
+            clock_milisec: 0,
+            scheduled: vec![],
+        }
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Change the master volume, keeping it between silence and full volume.
+    pub fn change_volume(&mut self, delta: f32) {
+        self.volume = (self.volume + delta).clamp(0., 1.);
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        if !self.muted {
+            self.backend.play(sound, self.volume);
+        }
+    }
+
+    pub fn play_later(&mut self, sound: Sound, delay_milisec: u32) {
+        self.scheduled
+            .push((sound, self.clock_milisec + delay_milisec));
+    }
+
+    /// Drop every sound not played yet, e.g. when the game they belong to is over.
+    pub fn cancel_scheduled(&mut self) {
+        self.scheduled.clear();
+    }
+
+    /// Play the scheduled sounds that came due.
+    pub fn update(&mut self, delta_milisec: u32) {
+        self.clock_milisec += delta_milisec;
+
+        let clock = self.clock_milisec;
+        let (due, later) = self.scheduled.iter().partition(|&&(_, at)| at <= clock);
+        self.scheduled = later;
+
+        for (sound, _) in due {
+            self.play(sound);
+        }
+    }
+
+    /// Play the sounds of everything an action did.
+    pub fn react(&mut self, outcome: &ActionOutcome) {
+        let exploded = outcome
+            .revealed
+            .iter()
+            .any(|reveal| matches!(reveal.object, Object::Mine(_)));
+
+        // tiles uncovered by the action itself start at depth 0, exploring starts next to it
+        let uncovered = match outcome.revealed.first() {
+            Some(reveal) if reveal.depth > 0 => Some(Sound::Chord),
+            Some(_) if outcome.revealed.len() > 1 => Some(Sound::Flood),
+            Some(_) => Some(Sound::Reveal),
+            None => None,
+        };
+
+        if exploded {
+            self.play(Sound::Explosion);
+        } else if let Some(sound) = uncovered {
+            self.play(sound);
+        }
+
+        // assists may flag many tiles at once, which is still a single sound
+        let flagged = outcome
+            .marks
+            .iter()
+            .any(|change| matches!(change.after, Mark::Flag(_)));
+        let unflagged = outcome.marks.iter().any(|change| {
+            matches!(change.before, Mark::Flag(_)) && !matches!(change.after, Mark::Flag(_))
+        });
+
+        if flagged {
+            self.play(Sound::Flag);
+        }
+        if unflagged {
+            self.play(Sound::Unflag);
+        }
+
+        if outcome.terminal == Some(Terminal::Victory) {
+            self.play(Sound::Victory);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::minefield::{MarkChange, Reveal};
+
+    /// A backend remembering what it was asked to play.
+    #[derive(Default, Clone)]
+    struct Recorder(Rc<RefCell<Vec<Sound>>>);
+
+    impl Backend for Recorder {
+        fn play(&mut self, sound: Sound, _volume: f32) {
+            self.0.borrow_mut().push(sound);
+        }
+    }
+
+    fn audio() -> (Audio, Recorder) {
+        let recorder = Recorder::default();
+        (Audio::new(Box::new(recorder.clone())), recorder)
+    }
+
+    fn played(outcome: &ActionOutcome) -> Vec<Sound> {
+        let (mut audio, recorder) = audio();
+        audio.react(outcome);
+        let played = recorder.0.borrow().clone();
+        played
+    }
+
+    fn reveal(object: Object, depth: usize) -> Reveal {
+        Reveal {
+            pos: (0, 0),
+            object,
+            depth,
+        }
+    }
+
+    fn mark(before: Mark, after: Mark) -> MarkChange {
+        MarkChange {
+            pos: (0, 0),
+            before,
+            after,
+        }
+    }
+
+    #[test]
+    fn uncovering_sounds_by_how_tiles_were_uncovered() {
+        let single = ActionOutcome {
+            revealed: vec![reveal(Object::Hint(1), 0)],
+            ..ActionOutcome::default()
+        };
+        let flood = ActionOutcome {
+            revealed: vec![reveal(Object::Blank, 0), reveal(Object::Hint(1), 1)],
+            ..ActionOutcome::default()
+        };
+        let chord = ActionOutcome {
+            revealed: vec![reveal(Object::Hint(2), 1), reveal(Object::Blank, 1)],
+            ..ActionOutcome::default()
+        };
+
+        assert_eq!(played(&single), [Sound::Reveal]);
+        assert_eq!(played(&flood), [Sound::Flood]);
+        assert_eq!(played(&chord), [Sound::Chord]);
+        assert!(played(&ActionOutcome::default()).is_empty());
+    }
+
+    #[test]
+    fn explosion_drowns_out_the_uncovering() {
+        let outcome = ActionOutcome {
+            revealed: vec![reveal(Object::Hint(1), 1), reveal(Object::Mine(1), 1)],
+            terminal: Some(Terminal::Defeat { triggered: (0, 0) }),
+            ..ActionOutcome::default()
+        };
+
+        assert_eq!(played(&outcome), [Sound::Explosion]);
+    }
+
+    #[test]
+    fn marking_sounds_flags_and_unflags_once() {
+        let flag = ActionOutcome {
+            marks: vec![
+                mark(Mark::None, Mark::Flag(1)),
+                mark(Mark::None, Mark::Flag(1)),
+            ],
+            ..ActionOutcome::default()
+        };
+        let unflag = ActionOutcome {
+            marks: vec![mark(Mark::Flag(1), Mark::Unsure)],
+            ..ActionOutcome::default()
+        };
+        let unsure = ActionOutcome {
+            marks: vec![mark(Mark::Unsure, Mark::None)],
+            ..ActionOutcome::default()
+        };
+
+        assert_eq!(played(&flag), [Sound::Flag]);
+        assert_eq!(played(&unflag), [Sound::Unflag]);
+        assert!(played(&unsure).is_empty());
+    }
+
+    #[test]
+    fn victory_follows_the_last_reveal() {
+        let outcome = ActionOutcome {
+            revealed: vec![reveal(Object::Hint(1), 0)],
+            terminal: Some(Terminal::Victory),
+            ..ActionOutcome::default()
+        };
+
+        assert_eq!(played(&outcome), [Sound::Reveal, Sound::Victory]);
+    }
+
+    #[test]
+    fn scheduled_sounds_play_once_due() {
+        let (mut audio, recorder) = audio();
+        audio.play_later(Sound::Explosion, 80);
+        audio.play_later(Sound::Explosion, 160);
+
+        audio.update(79);
+        assert!(recorder.0.borrow().is_empty());
+        audio.update(1);
+        assert_eq!(*recorder.0.borrow(), [Sound::Explosion]);
+        audio.update(100);
+        assert_eq!(*recorder.0.borrow(), [Sound::Explosion; 2]);
+    }
+
+    #[test]
+    fn muted_and_cancelled_sounds_stay_silent() {
+        let (mut audio, recorder) = audio();
+        audio.play_later(Sound::Explosion, 80);
+        audio.cancel_scheduled();
+        audio.update(100);
+
+        audio.toggle_mute();
+        audio.play(Sound::Flag);
+        assert!(recorder.0.borrow().is_empty());
+    }
+}
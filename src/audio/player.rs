@@ -0,0 +1,167 @@
+use std::env;
+use std::f32::consts::TAU;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use nanorand::{Rng, WyRand};
+
+use super::{Backend, Sound};
+
+/// Sample rate of the synthesized sounds.
+const SAMPLE_RATE: u32 = 22_050;
+
+/// Command-line players able to play a WAV file from their standard input,
+/// along with the arguments telling them to, in order of preference.
+const PLAYERS: [(&str, &[&str]); 3] = [
+    ("pw-play", &["-"]),
+    ("paplay", &[]),
+    ("aplay", &["-q", "-"]),
+];
+
+/// A backend handing synthesized sounds to a command-line player of the system,
+/// one short-lived process per sound, so that sounds overlap freely.
+#[derive(Debug)]
+pub struct PlayerBackend {
+    command: &'static str,
+    args: &'static [&'static str],
+}
+
+impl PlayerBackend {
+    /// The first known player found on the `PATH`, `None` if there is none.
+    pub fn detect() -> Option<Self> {
+        let path = env::var_os("PATH")?;
+
+        PLAYERS
+            .into_iter()
+            .find(|(command, _)| env::split_paths(&path).any(|dir| dir.join(command).is_file()))
+            .map(|(command, args)| Self { command, args })
+    }
+}
+
+impl Backend for PlayerBackend {
+    fn play(&mut self, sound: Sound, volume: f32) {
+        let wav = wav(&samples(sound, volume));
+
+        let child = Command::new(self.command)
+            .args(self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        // a sound failing to play is not worth interrupting the game for
+        let Ok(mut child) = child else {
+            return;
+        };
+
+        thread::spawn(move || {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(&wav);
+            }
+            let _ = child.wait();
+        });
+    }
+}
+
+/// What a sound is made of, played one note after the other.
+///
+/// Every note is a frequency in Hz and a duration in milliseconds,
+/// a frequency of 0 standing for white noise.
+fn notes(sound: Sound) -> &'static [(f32, u32)] {
+    match sound {
+        Sound::Reveal => &[(880., 40)],
+        Sound::Flood => &[(523., 50), (784., 90)],
+        Sound::Chord => &[(659., 40), (988., 60)],
+        Sound::Flag => &[(1319., 50)],
+        Sound::Unflag => &[(659., 50)],
+        Sound::Explosion => &[(0., 350)],
+        Sound::Victory => &[(523., 100), (659., 100), (784., 100), (1047., 250)],
+    }
+}
+
+/// The sound as 16-bit samples, scaled by `volume`.
+fn samples(sound: Sound, volume: f32) -> Vec<i16> {
+    let mut noise = WyRand::new_seed(0);
+    let mut samples = vec![];
+
+    for &(hz, milisec) in notes(sound) {
+        let count = SAMPLE_RATE * milisec / 1000;
+
+        samples.extend((0..count).map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            // every note fades out, so that it does not click when cut off
+            let fade = 1. - i as f32 / count as f32;
+            let wave = if hz > 0. {
+                (TAU * hz * t).sin()
+            } else {
+                noise.generate::<f32>() * 2. - 1.
+            };
+
+            (wave * fade * fade * volume * i16::MAX as f32 * 0.5) as i16
+        }));
+    }
+
+    samples
+}
+
+/// A mono WAV file holding the given samples.
+fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+
+    wav.extend(b"RIFF");
+    wav.extend((36 + data_len).to_le_bytes());
+    wav.extend(b"WAVE");
+
+    wav.extend(b"fmt ");
+    wav.extend(16u32.to_le_bytes());
+    wav.extend(1u16.to_le_bytes()); // PCM
+    wav.extend(1u16.to_le_bytes()); // mono
+    wav.extend(SAMPLE_RATE.to_le_bytes());
+    wav.extend((SAMPLE_RATE * 2).to_le_bytes()); // bytes per second
+    wav.extend(2u16.to_le_bytes()); // bytes per frame
+    wav.extend(16u16.to_le_bytes()); // bits per sample
+
+    wav.extend(b"data");
+    wav.extend(data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend(sample.to_le_bytes());
+    }
+
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wav_header_describes_the_samples() {
+        let samples = samples(Sound::Flag, 1.);
+        assert_eq!(samples.len(), (SAMPLE_RATE * 50 / 1000) as usize);
+
+        let wav = wav(&samples);
+        assert_eq!(wav.len(), 44 + samples.len() * 2);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(
+            u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize,
+            samples.len() * 2
+        );
+    }
+
+    #[test]
+    fn samples_scale_with_the_volume() {
+        let loudest = |volume| {
+            samples(Sound::Victory, volume)
+                .into_iter()
+                .map(i16::unsigned_abs)
+                .max()
+        };
+
+        assert_eq!(loudest(0.), Some(0));
+        assert!(loudest(0.5) < loudest(1.));
+    }
+}
@@ -0,0 +1,221 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nanorand::{RandomRange, WyRand};
+
+use crate::minefield::{Board, Cover, Object, Params, EXPERT};
+use crate::random::mix;
+
+/// Board every daily challenge is played on.
+pub const DAILY_PARAMS: Params = EXPERT;
+
+/// Days whose official attempt was already made, one day number per line,
+/// relative to the working directory.
+pub const ATTEMPTS_PATH: &str = "daily_attempts.txt";
+
+/// Where the last exported result summary is written,
+/// for platforms without clipboard support.
+pub const SHARE_PATH: &str = "daily_share.txt";
+
+/// Side of the square of tiles summarized by a single emoji in the shared grid.
+const SHARE_BLOCK: usize = 3;
+
+/// The challenge of a single day, the same for everyone playing on that day.
+///
+/// Days follow UTC, so that players in different time zones share the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Daily {
+    /// Days since the Unix epoch.
+    pub day: u64,
+}
+
+impl Daily {
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs());
+
+        Self { day: secs / 86_400 }
+    }
+
+    pub fn seed(self) -> u64 {
+        mix(self.day)
+    }
+
+    /// The tile every attempt starts by uncovering.
+    pub fn first_click(self) -> (usize, usize) {
+        let mut rng = WyRand::new_seed(mix(self.seed()));
+        (
+            RandomRange::random_range(&mut rng, 0..DAILY_PARAMS.width),
+            RandomRange::random_range(&mut rng, 0..DAILY_PARAMS.height),
+        )
+    }
+
+    /// Calendar date of the day, as `YYYY-MM-DD`.
+    pub fn date(self) -> String {
+        // Howard Hinnant's days-to-civil algorithm
+        let days = self.day as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// Whether the official attempt of the day was already made on this machine.
+    pub fn is_attempted(self) -> bool {
+        fs::read_to_string(ATTEMPTS_PATH).is_ok_and(|attempts| {
+            attempts
+                .lines()
+                .any(|line| line.trim().parse() == Ok(self.day))
+        })
+    }
+
+    /// Remember that the official attempt of the day was made.
+    pub fn record_attempt(self) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(ATTEMPTS_PATH)?;
+
+        writeln!(file, "{}", self.day)
+    }
+}
+
+/// Short text summary of a finished daily game, with the time, the 3BV/s
+/// of a won game and a coarse emoji map of how far the player got.
+pub fn share_text(daily: Daily, board: &Board, time_milisec: u32, official: bool) -> String {
+    let attempt = if official { "" } else { " (practice)" };
+    let mut text = format!("Enimdnal daily {}{attempt}\n", daily.date());
+
+    let time = time_milisec as f32 / 1000.;
+    if board.is_victory() {
+        let bbbv_per_sec = board.three_bv() as f32 / time.max(0.001);
+        text += &format!("cleared in {time:.3}s, 3BV/s {bbbv_per_sec:.2}\n");
+    } else {
+        text += &format!("blew up after {time:.3}s\n");
+    }
+
+    let (width, height) = board.dims();
+    for block_y in (0..height).step_by(SHARE_BLOCK) {
+        for block_x in (0..width).step_by(SHARE_BLOCK) {
+            let tiles: Vec<_> = (block_y..height.min(block_y + SHARE_BLOCK))
+                .flat_map(|y| (block_x..width.min(block_x + SHARE_BLOCK)).map(move |x| (x, y)))
+                .map(|(x, y)| board.tile(x, y))
+                .collect();
+
+            let exploded = tiles.iter().any(|tile| {
+                matches!(
+                    (tile.cover(), tile.object()),
+                    (Cover::Down, Object::Mine(_))
+                )
+            });
+            let safe = tiles.iter().filter(|tile| !tile.is_mine());
+            let cleared = safe
+                .clone()
+                .filter(|tile| matches!(tile.cover(), Cover::Down));
+
+            text += match (exploded, cleared.count(), safe.count()) {
+                (true, _, _) => "💥",
+                (false, cleared, safe) if cleared == safe => "🟩",
+                (false, 0, _) => "⬛",
+                _ => "🟨",
+            };
+        }
+        text += "\n";
+    }
+
+    text
+}
+
+/// A daily challenge being played.
+#[derive(Debug, Clone, Copy)]
+pub struct DailyRun {
+    pub daily: Daily,
+
+    /// Whether this is the one attempt of the day that counts,
+    /// later attempts on the same day are practice.
+    pub official: bool,
+
+    /// Whether the result of this attempt was exported.
+    pub shared: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::BEGINNER;
+
+    fn date(day: u64) -> String {
+        Daily { day }.date()
+    }
+
+    #[test]
+    fn date_starts_at_the_epoch() {
+        assert_eq!(date(0), "1970-01-01");
+    }
+
+    #[test]
+    fn date_has_leap_days() {
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(19_782), "2024-02-29");
+        // no leap day on a century that is not a multiple of 400
+        assert_eq!(date(47_540), "2100-02-28");
+        assert_eq!(date(47_541), "2100-03-01");
+    }
+
+    #[test]
+    fn date_rolls_over_years() {
+        assert_eq!(date(10_956), "1999-12-31");
+        assert_eq!(date(10_957), "2000-01-01");
+    }
+
+    /// A 6x3 board with a wall of mines down the third column.
+    fn walled() -> Board {
+        let mut board = Board::new(Params {
+            width: 6,
+            height: 3,
+            mines: 3,
+            ..BEGINNER
+        });
+        board.place_layout(&[(2, 0), (2, 1), (2, 2)]);
+        board
+    }
+
+    #[test]
+    fn share_text_maps_progress_by_block() {
+        let mut board = walled();
+        board.handle_primary_action(1, 0);
+
+        assert_eq!(
+            share_text(Daily { day: 0 }, &board, 2500, false),
+            "Enimdnal daily 1970-01-01 (practice)\nblew up after 2.500s\n🟨⬛\n"
+        );
+
+        board.handle_primary_action(2, 1);
+        assert!(share_text(Daily { day: 0 }, &board, 2500, true).ends_with("💥⬛\n"));
+    }
+
+    #[test]
+    fn share_text_reports_a_victory() {
+        let mut board = walled();
+        board.handle_primary_action(0, 0);
+        board.handle_primary_action(5, 0);
+
+        assert_eq!(
+            share_text(Daily { day: 0 }, &board, 4000, true),
+            "Enimdnal daily 1970-01-01\ncleared in 4.000s, 3BV/s 0.50\n🟩🟩\n"
+        );
+    }
+}
@@ -1,16 +1,34 @@
+mod confirm;
+mod defeat;
+mod editor;
+mod endless;
+pub(crate) mod hex;
+mod layered;
+pub(crate) mod panel;
+pub(crate) mod paused;
+mod puzzle_select;
+mod victory;
+pub(crate) mod widget;
+
 use notan::draw::*;
 use notan::math::{Mat3, Vec2};
 use notan::prelude::*;
 
-use crate::minefield::{Cover, Mark, Object, Params};
-use crate::state::defeat::{DefeatState, Explosion};
+use crate::minefield::{Cover, Hint, Mark, Object, Params, Topology};
+use crate::state::animation::{Animations, Effect, TileAnimation, REVEAL_MILISEC};
 use crate::state::{Stage, State};
+use widget::Ui;
 
 pub const TILE_SIZE: f32 = 40.;
 pub const HALF_TILE_SIZE: f32 = TILE_SIZE / 2.;
 pub const UI_WIDTH: f32 = 300.;
+/// Height the side panel needs, windows are never made shorter than this.
+const UI_HEIGHT: f32 = TILE_SIZE * 16.7;
+
+/// Thickness of the wrapped-around tile strips drawn around toroidal boards.
+const GHOST_SIZE: f32 = HALF_TILE_SIZE;
+const GHOST_ALPHA: f32 = 0.35;
 
-const DIMS: (f32, f32) = (TILE_SIZE, TILE_SIZE);
 const STROKE: f32 = 3.;
 
 const OUTLINE_COLOR: Color = Color::from_rgb(0., 0.8, 0.7);
@@ -28,73 +46,181 @@ const EXPLOSION_COLOR: Color = Color::from_rgb(1., 0.502, 0.);
 const EXPLOSION_STROKE: f32 = STROKE * 2.;
 const EXPLOSION_STROKE_COLOR: Color = Color::BLACK;
 
+/// What the side panel shows below the timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MineCounter {
+    /// Placed flags out of total mines, e.g. `012 / 099`.
+    FlagsOfMines,
+    /// Total mines minus placed flags, negative when overflagged.
+    MinesRemaining,
+    Hidden,
+}
+
+impl MineCounter {
+    pub fn cycle(&mut self) {
+        *self = match self {
+            Self::FlagsOfMines => Self::MinesRemaining,
+            Self::MinesRemaining => Self::Hidden,
+            Self::Hidden => Self::FlagsOfMines,
+        };
+    }
+}
+
 pub fn draw(gfx: &mut Graphics, state: &mut State) {
     let mut draw = gfx.create_draw();
 
     draw.clear(Color::BLACK);
 
-    draw_ui(&mut draw, state);
+    match state.stage() {
+        Stage::Endless(endless_state) => {
+            endless::draw(&mut draw, state, endless_state);
+            gfx.render(&draw);
+            return;
+        }
+        Stage::Editor(editor_state) => {
+            editor::draw(&mut draw, state, editor_state);
+            gfx.render(&draw);
+            return;
+        }
+        _ => (),
+    }
+
+    draw_panel(&mut draw, state);
+
+    let (origin_x, origin_y) = board_origin(state.board().params());
+    draw.transform()
+        .push(Mat3::from_translation(Vec2::new(origin_x, origin_y)));
 
     match state.stage() {
-        Stage::Paused => draw_paused(&mut draw, state),
-        _ => draw_board(&mut draw, state),
+        Stage::Paused(menu) => paused::draw(&mut draw, state, menu),
+        Stage::PuzzleSelect(selected) => puzzle_select::draw(&mut draw, state, *selected),
+        _ => {
+            draw_board(&mut draw, state);
+            draw_ghosts(&mut draw, state);
+            layered::draw_previews(&mut draw, state);
+            victory::draw_celebration(&mut draw, state);
+            draw_explosions(
+                &mut draw,
+                state.board().params(),
+                state.layer(),
+                state.animations(),
+            );
+        }
     }
 
-    if let Stage::Defeat(defeat_state) = state.stage() {
-        draw_explosions(&mut draw, defeat_state);
+    match state.stage() {
+        Stage::Victory(victory_state) => victory::draw_summary(&mut draw, state, victory_state),
+        Stage::Defeat(post_mortem) => defeat::draw_post_mortem(&mut draw, state, post_mortem),
+        Stage::ConfirmRestart(restart) => confirm::draw_prompt(&mut draw, state, *restart),
+        _ => (),
     }
 
+    draw.transform().pop();
+
     gfx.render(&draw);
 }
 
+/// Size of a window fitting both the board and the side panel.
+pub fn window_size(params: Params) -> (f32, f32) {
+    let (board_width, board_height) = board_dims(params);
+    (board_width + UI_WIDTH, board_height.max(UI_HEIGHT))
+}
+
+/// Screen space taken by the board, including any decorations around it.
 pub fn board_dims(params: Params) -> (f32, f32) {
+    let (origin_x, origin_y) = board_origin(params);
+    let (grid_width, grid_height) = grid_size(params);
+    let previews_height = layered::previews_height(params);
     (
-        params.width as f32 * TILE_SIZE,
-        params.height as f32 * TILE_SIZE,
+        grid_width + 2. * origin_x,
+        grid_height + 2. * origin_y + previews_height,
     )
 }
 
+/// Screen space taken by the tiles alone, of a single layer for layered boards.
+fn grid_size(params: Params) -> (f32, f32) {
+    match params.topology {
+        Topology::Bounded | Topology::Toroidal | Topology::Layered(_) => (
+            params.width as f32 * TILE_SIZE,
+            params.height as f32 * TILE_SIZE,
+        ),
+        Topology::Hex => hex::grid_size((params.width, params.height)),
+    }
+}
+
+/// Screen position of the top-left corner of the tile at `(0, 0)`.
+pub fn board_origin(params: Params) -> (f32, f32) {
+    match params.topology {
+        Topology::Bounded => (0., 0.),
+        Topology::Toroidal => (GHOST_SIZE, GHOST_SIZE),
+        Topology::Hex | Topology::Layered(_) => (0., 0.),
+    }
+}
+
+/// Screen position of the center of a tile, relative to [board_origin].
+///
+/// Tiles of a layered board are placed as if their layer was the one on the screen.
+pub fn tile_center(params: Params, x: usize, y: usize) -> (f32, f32) {
+    match params.topology {
+        Topology::Bounded | Topology::Toroidal | Topology::Layered(_) => (
+            x as f32 * TILE_SIZE + HALF_TILE_SIZE,
+            params.row_in_layer(y) as f32 * TILE_SIZE + HALF_TILE_SIZE,
+        ),
+        Topology::Hex => hex::center(x, y),
+    }
+}
+
+/// Draw the shape of a single tile, either filled or as an outline of the given stroke width.
+///
+/// `scale` grows or shrinks the shape around its center.
+fn draw_tile_shape(
+    draw: &mut Draw,
+    topology: Topology,
+    (center_x, center_y): (f32, f32),
+    scale: f32,
+    color: Color,
+    stroke: Option<f32>,
+) {
+    match topology {
+        Topology::Bounded | Topology::Toroidal | Topology::Layered(_) => {
+            let size = TILE_SIZE * scale;
+            let pos = (center_x - size / 2., center_y - size / 2.);
+
+            let mut rect = draw.rect(pos, (size, size));
+            rect.color(color);
+            if let Some(width) = stroke {
+                rect.stroke(width);
+            }
+        }
+        Topology::Hex => {
+            let mut hex = draw.polygon(6, hex::HEX_RADIUS * scale);
+            hex.position(center_x, center_y).color(color);
+            if let Some(width) = stroke {
+                hex.stroke(width);
+            }
+        }
+    }
+}
+
 fn draw_board(draw: &mut Draw, state: &State) {
-    let (cols, rows) = state.board().dims();
+    let params = state.board().params();
 
-    for y in 0..rows {
-        for x in 0..cols {
+    for y in params.layer_rows(state.layer()) {
+        for x in 0..params.width {
             draw_tile(draw, state, x, y);
         }
     }
 }
 
 fn draw_tile(draw: &mut Draw, state: &State, x: usize, y: usize) {
-    let screen_x = x as f32 * TILE_SIZE;
-    let screen_y = y as f32 * TILE_SIZE;
-    let pos = (screen_x, screen_y);
+    let params = state.board().params();
+    let center @ (center_x, center_y) = tile_center(params, x, y);
 
     let tile = state.board().tile(x, y);
     let cover = tile.cover();
     let object = tile.object();
 
-    let mut fill_color = match (cover, object) {
-        (Cover::Up(Mark::None), _) => COVER_COLOR,
-        (Cover::Up(Mark::Flag), _) => FLAG_COLOR,
-        (Cover::Up(Mark::Unsure), _) => UNSURE_COLOR,
-        (Cover::Down, Object::Blank) => BLANK_COLOR,
-        (Cover::Down, Object::Hint(_)) => HINT_COLOR,
-        (Cover::Down, Object::Mine) => MINE_COLOR,
-    };
-
-    match state.stage() {
-        Stage::Defeat(_) => {
-            if let Object::Mine = object {
-                fill_color = MINE_COLOR;
-            }
-        }
-        Stage::Victory => {
-            if let Cover::Up(_) = cover {
-                fill_color = WIN_COLOR;
-            }
-        }
-        _ => (),
-    }
+    let mut fill_color = tile_color(state, x, y);
 
     if let Some(hover_coords) = state.hover_index() {
         if (x, y) == hover_coords && matches!(cover, Cover::Up(_)) {
@@ -102,108 +228,203 @@ fn draw_tile(draw: &mut Draw, state: &State, x: usize, y: usize) {
         }
     }
 
-    draw.rect(pos, DIMS).color(fill_color);
-    draw.rect(pos, DIMS).color(OUTLINE_COLOR).stroke(STROKE);
+    // a tile waiting for its turn in a reveal wave still looks covered, then grows out of it
+    let reveal = reveal_progress(state, x, y);
+    if let Some(progress) = reveal {
+        draw_tile_shape(draw, params.topology, center, 1., COVER_COLOR, None);
+        draw_tile_shape(draw, params.topology, center, progress, fill_color, None);
+    } else {
+        draw_tile_shape(draw, params.topology, center, 1., fill_color, None);
+    }
+
+    draw_tile_shape(
+        draw,
+        params.topology,
+        center,
+        1.,
+        OUTLINE_COLOR,
+        Some(STROKE),
+    );
+
+    let hinted = match state.hint() {
+        Some(Hint::Safe(pos)) if pos == (x, y) => Some(WIN_COLOR),
+        Some(Hint::Mine(pos)) if pos == (x, y) => Some(FLAG_COLOR),
+        Some(Hint::Guess(pos)) if pos == (x, y) => Some(UNSURE_COLOR),
+        _ => None,
+    };
+
+    if let Some(color) = hinted {
+        draw_tile_shape(draw, params.topology, center, 0.8, color, Some(STROKE * 2.));
+    }
+
+    if reveal.is_some() {
+        return;
+    }
+
+    // quantities only need spelling out when a tile may hold more than one mine
+    let multi = params.mines_per_tile > 1;
+    let shows_mine = matches!(cover, Cover::Down) || matches!(state.stage(), Stage::Defeat(_));
+    let label = match (cover, object) {
+        (Cover::Down, Object::Hint(n)) => Some((n, Color::BLACK)),
+        (_, Object::Mine(n)) if multi && shows_mine => Some((n, Color::WHITE)),
+        (Cover::Up(Mark::Flag(n)), _) if multi => Some((n, Color::WHITE)),
+        _ => None,
+    };
 
-    if let (Cover::Down, Object::Hint(n)) = (cover, object) {
+    if let Some((n, color)) = label {
         draw.text(state.font(), &n.to_string())
-            .color(Color::BLACK)
+            .color(color)
             .size(26.0)
-            .position(screen_x + HALF_TILE_SIZE, screen_y + HALF_TILE_SIZE)
+            .position(center_x, center_y)
             .h_align_center()
             .v_align_middle();
     }
 }
 
-fn hover_color(color: &mut Color) {
-    let Color { r, g, b, .. } = color;
+/// How far along the tile is in uncovering, from 0 to 1, `None` when it is not animated.
+fn reveal_progress(state: &State, x: usize, y: usize) -> Option<f32> {
+    let animations = state.animations();
+    let reveal = animations.on_tile((x, y), Effect::Reveal)?;
+    let elapsed = animations.elapsed_milisec(reveal).unwrap_or(0);
 
-    *r *= 0.8;
-    *g *= 0.8;
-    *b *= 0.8;
+    Some(f32::min(elapsed as f32 / REVEAL_MILISEC as f32, 1.))
 }
 
-fn draw_paused(draw: &mut Draw, state: &State) {
-    let (cols, rows) = state.board().dims();
-    let size @ (width, height) = (cols as f32 * TILE_SIZE, rows as f32 * TILE_SIZE);
+fn tile_color(state: &State, x: usize, y: usize) -> Color {
+    let tile = state.board().tile(x, y);
+    let cover = tile.cover();
+    let object = tile.object();
 
-    draw.rect((0., 0.), size).color(COVER_COLOR);
-    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+    let fill_color = base_color(cover, object);
 
-    draw.text(state.font(), "PAUSED")
-        .color(Color::WHITE)
-        .size(40.)
-        .position(width / 2., height / 2.)
-        .h_align_center()
-        .v_align_middle();
+    match (state.stage(), cover, object) {
+        // mines that were found stay flagged, to tell them apart from the missed ones
+        (Stage::Defeat(_), Cover::Up(Mark::Flag(_)), Object::Mine(_)) => FLAG_COLOR,
+        (Stage::Defeat(_), _, Object::Mine(_)) => MINE_COLOR,
+        // mines survived in a game that went on
+        (_, Cover::Down, Object::Mine(_)) => EXPLOSION_COLOR,
+        (Stage::Victory(_), Cover::Up(_), _) => WIN_COLOR,
+        _ => fill_color,
+    }
 }
 
-fn draw_ui(draw: &mut Draw, state: &State) {
-    let (cols, _) = state.board().dims();
+/// Color of a tile based on its own state alone, regardless of the game stage.
+fn base_color(cover: Cover, object: Object) -> Color {
+    match (cover, object) {
+        (Cover::Up(Mark::None), _) => COVER_COLOR,
+        (Cover::Up(Mark::Flag(_)), _) => FLAG_COLOR,
+        (Cover::Up(Mark::Unsure), _) => UNSURE_COLOR,
+        (Cover::Down, Object::Blank) => BLANK_COLOR,
+        (Cover::Down, Object::Hint(_)) => HINT_COLOR,
+        (Cover::Down, Object::Mine(_)) => MINE_COLOR,
+    }
+}
 
-    draw.transform().push(Mat3::from_translation(Vec2::new(
-        cols as f32 * TILE_SIZE,
-        0.,
-    )));
+/// Faint copies of the opposite edges, drawn around a toroidal board,
+/// so that wrapped-around neighbors of the edge tiles are visible.
+fn draw_ghosts(draw: &mut Draw, state: &State) {
+    if state.board().params().topology != Topology::Toroidal {
+        return;
+    }
 
-    let elapsed = state.run_timer_milisec();
+    let (cols, rows) = state.board().dims();
+    let (cols, rows) = (cols as i32, rows as i32);
 
-    let milis = elapsed % 1000;
-    let secs = (elapsed / 1000) % 60;
-    let mins = elapsed / 60_000;
+    // screen span of a ghost-or-regular row/column along a single axis
+    let span = |i: i32, len: i32| match i {
+        -1 => (-GHOST_SIZE, GHOST_SIZE),
+        i if i == len => (len as f32 * TILE_SIZE, GHOST_SIZE),
+        i => (i as f32 * TILE_SIZE, TILE_SIZE),
+    };
 
-    let time = format!("{:02}:{:02}.{:03}", mins, secs, milis);
+    for y in -1..=rows {
+        for x in -1..=cols {
+            let is_ghost = x < 0 || y < 0 || x == cols || y == rows;
+            if !is_ghost {
+                continue;
+            }
 
-    draw.text(state.font_mono(), &time)
-        .color(Color::WHITE)
-        .size(30.)
-        .position(UI_WIDTH / 2., TILE_SIZE)
-        .h_align_center()
-        .v_align_middle();
+            let (wrapped_x, wrapped_y) = (x.rem_euclid(cols) as usize, y.rem_euclid(rows) as usize);
+            let (screen_x, width) = span(x, cols);
+            let (screen_y, height) = span(y, rows);
+
+            draw.rect((screen_x, screen_y), (width, height))
+                .color(tile_color(state, wrapped_x, wrapped_y))
+                .alpha(GHOST_ALPHA);
+
+            let tile = state.board().tile(wrapped_x, wrapped_y);
+            if let (Cover::Down, Object::Hint(n)) = (tile.cover(), tile.object()) {
+                draw.text(state.font(), &n.to_string())
+                    .color(Color::WHITE)
+                    .alpha(GHOST_ALPHA)
+                    .size(14.)
+                    .position(screen_x + width / 2., screen_y + height / 2.)
+                    .h_align_center()
+                    .v_align_middle();
+            }
+        }
+    }
+}
+
+fn hover_color(color: &mut Color) {
+    let Color { r, g, b, .. } = color;
+
+    *r *= 0.8;
+    *g *= 0.8;
+    *b *= 0.8;
+}
 
-    let flags = state.board().flags();
-    let mines = state.board().mines();
+fn draw_panel(draw: &mut Draw, state: &State) {
+    let (board_width, _) = board_dims(state.board().params());
 
-    let flag_counter = format!("{:03} / {:03}", flags, mines);
+    draw.transform()
+        .push(Mat3::from_translation(Vec2::new(board_width, 0.)));
 
-    draw.text(state.font_mono(), &flag_counter)
-        .color(Color::WHITE)
-        .size(30.)
-        .position(UI_WIDTH / 2., TILE_SIZE * 3.)
-        .h_align_center()
-        .v_align_middle();
+    let mut ui = Ui::render(draw, state.font_mono(), state.pointer(), (board_width, 0.));
+    panel::show(&mut ui, state);
 
     draw.transform().pop();
 }
 
-fn draw_explosions(draw: &mut Draw, defeat_state: &DefeatState) {
-    for explosion in &defeat_state.explosions {
-        draw_explosion(draw, explosion, defeat_state.elapsed_milisec);
+fn format_time(milisec: u32) -> String {
+    let milis = milisec % 1000;
+    let secs = (milisec / 1000) % 60;
+    let mins = milisec / 60_000;
+
+    format!("{:02}:{:02}.{:03}", mins, secs, milis)
+}
+
+fn draw_explosions(draw: &mut Draw, params: Params, layer: usize, animations: &Animations) {
+    for explosion in animations.with_effect(Effect::Explosion) {
+        if params.layer_of(explosion.pos.1) != layer {
+            continue;
+        }
+
+        if let Some(elapsed) = animations.elapsed_milisec(explosion) {
+            draw_explosion(draw, params, explosion, elapsed);
+        }
     }
 }
 
-fn draw_explosion(draw: &mut Draw, explosion: &Explosion, elapsed: u32) {
+fn draw_explosion(draw: &mut Draw, params: Params, explosion: &TileAnimation, elapsed: u32) {
     const ANIMATION_DURATION: f32 = 100.;
 
-    let Some(elapsed) = u32::checked_sub(elapsed, explosion.delay) else {
-        return;
-    };
-
     let progress = elapsed as f32 / ANIMATION_DURATION;
     let magnify = gauss(progress, 3., 0., 1.);
-    let shift = TILE_SIZE * magnify;
+    let scale = 1. + magnify;
 
     let (expl_x, expl_y) = explosion.pos;
-    let position = (
-        TILE_SIZE * expl_x as f32 - shift / 2.,
-        TILE_SIZE * expl_y as f32 - shift / 2.,
+    let center = tile_center(params, expl_x, expl_y);
+
+    draw_tile_shape(draw, params.topology, center, scale, EXPLOSION_COLOR, None);
+    draw_tile_shape(
+        draw,
+        params.topology,
+        center,
+        scale,
+        EXPLOSION_STROKE_COLOR,
+        Some(EXPLOSION_STROKE),
     );
-    let size = (TILE_SIZE + shift, TILE_SIZE + shift);
-
-    draw.rect(position, size).color(EXPLOSION_COLOR);
-    draw.rect(position, size)
-        .color(EXPLOSION_STROKE_COLOR)
-        .stroke(EXPLOSION_STROKE);
 }
 
 fn gauss(x: f32, a: f32, b: f32, c: f32) -> f32 {
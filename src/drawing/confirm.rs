@@ -0,0 +1,45 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{grid_size, OUTLINE_COLOR};
+use crate::state::confirm::Restart;
+use crate::state::State;
+
+const LINE_HEIGHT: f32 = 26.;
+
+/// Question covering the board, before abandoning the game on it.
+///
+/// The clock stands still meanwhile, so nothing of the board is left to study.
+pub fn draw_prompt(draw: &mut Draw, state: &State, restart: Restart) {
+    let size @ (width, height) = grid_size(state.board().params());
+
+    draw.rect((0., 0.), size).color(Color::BLACK);
+    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+
+    let question = match restart {
+        Restart::NewGame => "Abandon for a new game?",
+        Restart::SameLayout => "Start this board over?",
+        Restart::Abandon => "Abandon this game?",
+    };
+
+    draw.text(state.font(), question)
+        .color(Color::WHITE)
+        .size(24.)
+        .position(width / 2., height / 2. - LINE_HEIGHT)
+        .h_align_center()
+        .v_align_middle();
+
+    draw.text(state.font_mono(), "Y / Return: yes")
+        .color(Color::GRAY)
+        .size(16.)
+        .position(width / 2., height / 2. + LINE_HEIGHT)
+        .h_align_center()
+        .v_align_middle();
+
+    draw.text(state.font_mono(), "N / Escape: keep playing")
+        .color(Color::GRAY)
+        .size(16.)
+        .position(width / 2., height / 2. + LINE_HEIGHT * 2.)
+        .h_align_center()
+        .v_align_middle();
+}
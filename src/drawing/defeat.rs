@@ -0,0 +1,99 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{grid_size, tile_center, OUTLINE_COLOR, STROKE, TILE_SIZE};
+use crate::minefield::{Cover, Mark};
+use crate::state::animation::Effect;
+use crate::state::defeat::{PostMortem, Verdict};
+use crate::state::State;
+
+/// Time the explosions get to play out before the post-mortem covers them.
+const EXPLOSIONS_MILISEC: u32 = 400;
+
+const LINE_HEIGHT: f32 = 22.;
+
+/// Crosses over the wrong flags, and a few lines on the fatal click along the bottom of the board.
+pub fn draw_post_mortem(draw: &mut Draw, state: &State, post_mortem: &PostMortem) {
+    if !state
+        .animations()
+        .settled(Effect::Explosion, EXPLOSIONS_MILISEC)
+    {
+        return;
+    }
+
+    draw_wrong_flags(draw, state);
+
+    let mut lines = vec![format!(
+        "{} wrong flags, {} mines not found",
+        post_mortem.wrong_flags, post_mortem.missed_mines
+    )];
+
+    if let Some(fatal_click) = post_mortem.fatal_click {
+        let verdict = match fatal_click.verdict {
+            Verdict::MisplacedFlag => "a wrong flag misled the exploring",
+            Verdict::KnownMine => "the hints proved it was a mine",
+            Verdict::NeedlessGuess => "a guess, with a safe tile provable",
+            Verdict::ForcedGuess => "a forced guess, nothing was safe",
+            Verdict::AssistMisled => "an assist trusted a wrong flag",
+        };
+
+        lines.push(verdict.to_owned());
+        lines.push(format!(
+            "it was a mine with {:.0}% odds",
+            fatal_click.mine_probability * 100.
+        ));
+    }
+
+    if state.puzzle().is_none() && state.daily().is_none() {
+        lines.push("L: retry this board, as practice".to_owned());
+    }
+
+    let (width, height) = grid_size(state.board().params());
+    let band_height = LINE_HEIGHT * (lines.len() as f32 + 1.);
+    let top = height - band_height;
+
+    draw.rect((0., top), (width, band_height))
+        .color(Color::BLACK)
+        .alpha(0.75);
+    draw.rect((0., top), (width, band_height))
+        .color(OUTLINE_COLOR)
+        .stroke(STROKE);
+
+    for (i, line) in lines.iter().enumerate() {
+        draw.text(state.font_mono(), line)
+            .color(Color::WHITE)
+            .size(14.)
+            .position(width / 2., top + LINE_HEIGHT * (i as f32 + 1.))
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+/// A cross over every flag on the current layer that was placed on too few mines.
+fn draw_wrong_flags(draw: &mut Draw, state: &State) {
+    let params = state.board().params();
+    let arm = TILE_SIZE / 4.;
+
+    for y in params.layer_rows(state.layer()) {
+        for x in 0..params.width {
+            let tile = state.board().tile(x, y);
+            let Cover::Up(Mark::Flag(flags)) = tile.cover() else {
+                continue;
+            };
+
+            if flags as usize <= tile.mines() {
+                continue;
+            }
+
+            let (center_x, center_y) = tile_center(params, x, y);
+            for (dx, dy) in [(arm, arm), (arm, -arm)] {
+                draw.line(
+                    (center_x - dx, center_y - dy),
+                    (center_x + dx, center_y + dy),
+                )
+                .color(Color::BLACK)
+                .width(STROKE);
+            }
+        }
+    }
+}
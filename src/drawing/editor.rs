@@ -0,0 +1,127 @@
+use notan::draw::*;
+use notan::math::{Mat3, Vec2};
+use notan::prelude::*;
+
+use super::{
+    base_color, board_dims, hover_color, Cover, Object, COVER_COLOR, MINE_COLOR, OUTLINE_COLOR,
+    STROKE, TILE_SIZE, UI_WIDTH, WIN_COLOR,
+};
+use crate::puzzle::MAX_DIFFICULTY;
+use crate::state::editor::EditorState;
+use crate::state::State;
+
+/// Draw the puzzle being designed, with every hint visible,
+/// and the editor controls in the side panel.
+pub fn draw(draw: &mut Draw, state: &State, editor: &EditorState) {
+    let (cols, rows) = state.board().dims();
+
+    for y in 0..rows {
+        for x in 0..cols {
+            draw_tile(draw, state, editor, x, y);
+        }
+    }
+
+    let (board_width, _) = board_dims(state.board().params());
+    draw_ui(draw, state, editor, board_width);
+}
+
+fn draw_tile(draw: &mut Draw, state: &State, editor: &EditorState, x: usize, y: usize) {
+    let object = state.board().tile(x, y).object();
+    let uncovered = editor.puzzle.is_uncovered(x, y);
+    let pos = (x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+
+    let mut fill_color = match object {
+        Object::Mine(_) => MINE_COLOR,
+        _ if uncovered => base_color(Cover::Down, object),
+        _ => COVER_COLOR,
+    };
+
+    if state.hover_index() == Some((x, y)) {
+        hover_color(&mut fill_color);
+    }
+
+    draw.rect(pos, (TILE_SIZE, TILE_SIZE)).color(fill_color);
+    draw.rect(pos, (TILE_SIZE, TILE_SIZE))
+        .color(OUTLINE_COLOR)
+        .stroke(STROKE);
+
+    // hints of tiles starting covered are shown too, to see patterns as they form
+    if let Object::Hint(n) = object {
+        draw.text(state.font(), &n.to_string())
+            .color(if uncovered {
+                Color::BLACK
+            } else {
+                Color::WHITE
+            })
+            .size(26.0)
+            .position(pos.0 + TILE_SIZE / 2., pos.1 + TILE_SIZE / 2.)
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+fn draw_ui(draw: &mut Draw, state: &State, editor: &EditorState, board_width: f32) {
+    draw.transform()
+        .push(Mat3::from_translation(Vec2::new(board_width, 0.)));
+
+    draw.text(state.font(), "EDITOR")
+        .color(Color::WHITE)
+        .size(30.)
+        .position(UI_WIDTH / 2., TILE_SIZE)
+        .h_align_center()
+        .v_align_middle();
+
+    let summary = format!(
+        "mines {:03}  difficulty {}/{MAX_DIFFICULTY}",
+        editor.puzzle.mines().len(),
+        editor.puzzle.difficulty
+    );
+
+    draw.text(state.font_mono(), &summary)
+        .color(Color::WHITE)
+        .size(16.)
+        .position(UI_WIDTH / 2., TILE_SIZE * 3.)
+        .h_align_center()
+        .v_align_middle();
+
+    let (verdict, color) = match editor.solvable {
+        Some(true) => ("solvable without guessing", WIN_COLOR),
+        Some(false) => ("needs guessing", Color::RED),
+        None => ("V: run the solver", Color::GRAY),
+    };
+
+    draw.text(state.font_mono(), verdict)
+        .color(color)
+        .size(16.)
+        .position(UI_WIDTH / 2., TILE_SIZE * 4.)
+        .h_align_center()
+        .v_align_middle();
+
+    if let Some(status) = &editor.status {
+        draw.text(state.font_mono(), status)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(UI_WIDTH / 2., TILE_SIZE * 5.)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    let help = [
+        "left click: mine",
+        "right click: start uncovered",
+        "1-5: difficulty",
+        "S: save to custom pack",
+        "ESC: leave the editor",
+    ];
+
+    for (i, line) in help.into_iter().enumerate() {
+        draw.text(state.font_mono(), line)
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., TILE_SIZE * (6.5 + 0.6 * i as f32))
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    draw.transform().pop();
+}
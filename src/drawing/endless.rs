@@ -0,0 +1,131 @@
+use notan::draw::*;
+use notan::math::{Mat3, Vec2};
+use notan::prelude::*;
+
+use super::{
+    base_color, board_dims, hover_color, Cover, Object, EXPLOSION_COLOR, EXPLOSION_STROKE,
+    EXPLOSION_STROKE_COLOR, MINE_COLOR, OUTLINE_COLOR, STROKE, TILE_SIZE, UI_WIDTH,
+};
+use crate::minefield::{Mark, Tile};
+use crate::state::endless::EndlessState;
+use crate::state::State;
+
+/// Draw an endless game, in the same screen area the classic board would take.
+pub fn draw(draw: &mut Draw, state: &State, endless: &EndlessState) {
+    let view = board_dims(state.board().params());
+
+    draw_board(draw, state, endless, view);
+
+    // tiles on the edge of the view spill over, keep them out of the side panel
+    let (view_width, view_height) = view;
+    draw.rect((view_width, 0.), (UI_WIDTH, view_height))
+        .color(Color::BLACK);
+
+    draw_ui(draw, state, endless, view_width);
+}
+
+fn draw_board(draw: &mut Draw, state: &State, endless: &EndlessState, view: (f32, f32)) {
+    let (first_x, first_y) = endless.screen_to_tile(view, (0., 0.));
+    let (last_x, last_y) = endless.screen_to_tile(view, view);
+
+    for y in first_y..=last_y {
+        for x in first_x..=last_x {
+            let pos = endless.tile_to_screen(view, (x, y));
+            let tile = endless.board.tile(x, y);
+
+            draw_tile(draw, state, endless, (x, y), tile, pos);
+        }
+    }
+
+    if let Some(exploded) = endless.board.exploded() {
+        let (pos_x, pos_y) = endless.tile_to_screen(view, exploded);
+
+        draw.rect((pos_x, pos_y), (TILE_SIZE, TILE_SIZE))
+            .color(EXPLOSION_COLOR);
+        draw.rect((pos_x, pos_y), (TILE_SIZE, TILE_SIZE))
+            .color(EXPLOSION_STROKE_COLOR)
+            .stroke(EXPLOSION_STROKE);
+    }
+}
+
+fn draw_tile(
+    draw: &mut Draw,
+    state: &State,
+    endless: &EndlessState,
+    coords: (i64, i64),
+    // tiles of ungenerated chunks look exactly like untouched ones
+    tile: Option<Tile>,
+    (pos_x, pos_y): (f32, f32),
+) {
+    let (cover, object) = tile.map_or((Cover::Up(Mark::None), Object::Blank), |tile| {
+        (tile.cover(), tile.object())
+    });
+
+    let mut fill_color = match object {
+        Object::Mine(_) if endless.board.exploded().is_some() => MINE_COLOR,
+        _ => base_color(cover, object),
+    };
+
+    if endless.hover == Some(coords) && matches!(cover, Cover::Up(_)) {
+        hover_color(&mut fill_color);
+    }
+
+    draw.rect((pos_x, pos_y), (TILE_SIZE, TILE_SIZE))
+        .color(fill_color);
+    draw.rect((pos_x, pos_y), (TILE_SIZE, TILE_SIZE))
+        .color(OUTLINE_COLOR)
+        .stroke(STROKE);
+
+    if let (Cover::Down, Object::Hint(n)) = (cover, object) {
+        draw.text(state.font(), &n.to_string())
+            .color(Color::BLACK)
+            .size(26.0)
+            .position(pos_x + TILE_SIZE / 2., pos_y + TILE_SIZE / 2.)
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+fn draw_ui(draw: &mut Draw, state: &State, endless: &EndlessState, view_width: f32) {
+    draw.transform()
+        .push(Mat3::from_translation(Vec2::new(view_width, 0.)));
+
+    draw.text(state.font(), "ENDLESS")
+        .color(Color::WHITE)
+        .size(30.)
+        .position(UI_WIDTH / 2., TILE_SIZE)
+        .h_align_center()
+        .v_align_middle();
+
+    draw.text(
+        state.font_mono(),
+        &format!("cleared {:05}", endless.board.cleared()),
+    )
+    .color(Color::WHITE)
+    .size(30.)
+    .position(UI_WIDTH / 2., TILE_SIZE * 3.)
+    .h_align_center()
+    .v_align_middle();
+
+    if endless.board.exploded().is_some() {
+        draw.text(state.font(), "SPACE: new board")
+            .color(EXPLOSION_COLOR)
+            .size(20.)
+            .position(UI_WIDTH / 2., TILE_SIZE * 5.)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    let help = ["arrows / WASD: pan", "ESC: back to classic"];
+
+    for (i, line) in help.into_iter().enumerate() {
+        draw.text(state.font_mono(), line)
+            .color(Color::GRAY)
+            .size(16.)
+            .position(UI_WIDTH / 2., TILE_SIZE * (10. + 0.6 * i as f32))
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    draw.transform().pop();
+}
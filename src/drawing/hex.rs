@@ -0,0 +1,67 @@
+use super::TILE_SIZE;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Distance from the center of a hex tile to any of its corners.
+///
+/// Hex tiles are pointy-topped, and as wide as a square tile is.
+pub const HEX_RADIUS: f32 = TILE_SIZE / SQRT_3;
+
+/// Vertical distance between the centers of two consecutive rows.
+const ROW_HEIGHT: f32 = HEX_RADIUS * 1.5;
+
+/// Screen space taken by a hex grid, with odd rows shifted right by half a tile.
+pub fn grid_size((cols, rows): (usize, usize)) -> (f32, f32) {
+    (
+        TILE_SIZE * (cols as f32 + 0.5),
+        2. * HEX_RADIUS + ROW_HEIGHT * rows.saturating_sub(1) as f32,
+    )
+}
+
+pub fn center(x: usize, y: usize) -> (f32, f32) {
+    let row_shift = (y % 2) as f32 * 0.5;
+    (
+        TILE_SIZE * (x as f32 + 0.5 + row_shift),
+        HEX_RADIUS + ROW_HEIGHT * y as f32,
+    )
+}
+
+/// Hit-test a screen position against a hex grid.
+///
+/// Works by converting the position to fractional cube coordinates
+/// and rounding it to the nearest hex, which is exact for the whole tile area.
+pub fn screen_to_tile(
+    (cols, rows): (usize, usize),
+    (screen_x, screen_y): (f32, f32),
+) -> Option<(usize, usize)> {
+    // relative to the center of the tile at (0, 0)
+    let rel_x = screen_x - TILE_SIZE / 2.;
+    let rel_y = screen_y - HEX_RADIUS;
+
+    let q = (SQRT_3 / 3. * rel_x - rel_y / 3.) / HEX_RADIUS;
+    let r = (2. / 3. * rel_y) / HEX_RADIUS;
+    let s = -q - r;
+
+    let (mut round_q, round_s, mut round_r) = (q.round(), s.round(), r.round());
+    let diff_q = (round_q - q).abs();
+    let diff_r = (round_r - r).abs();
+    let diff_s = (round_s - s).abs();
+
+    if diff_q > diff_r && diff_q > diff_s {
+        round_q = -round_s - round_r;
+    } else if diff_r > diff_s {
+        round_r = -round_q - round_s;
+    }
+
+    let row = round_r as i32;
+    let col = round_q as i32 + (row - (row & 1)) / 2;
+
+    let col_in_bounds = col >= 0 && col < cols as i32;
+    let row_in_bounds = row >= 0 && row < rows as i32;
+
+    if !col_in_bounds || !row_in_bounds {
+        return None;
+    }
+
+    Some((col as _, row as _))
+}
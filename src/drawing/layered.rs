@@ -0,0 +1,70 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{grid_size, tile_color, GHOST_ALPHA, HALF_TILE_SIZE, TILE_SIZE};
+use crate::minefield::{Params, Topology};
+use crate::state::State;
+
+/// Size of the previews of adjacent layers, relative to the layer on the screen.
+const PREVIEW_SCALE: f32 = 0.25;
+
+/// Space around the previews, which also holds their labels.
+const PREVIEW_MARGIN: f32 = HALF_TILE_SIZE;
+
+/// Height of the strip below a layered board where adjacent layers are previewed,
+/// nothing for flat boards.
+pub fn previews_height(params: Params) -> f32 {
+    match params.topology {
+        Topology::Layered(_) => {
+            params.height as f32 * TILE_SIZE * PREVIEW_SCALE + 2. * PREVIEW_MARGIN
+        }
+        Topology::Bounded | Topology::Toroidal | Topology::Hex => 0.,
+    }
+}
+
+/// Faded, scaled-down copies of the layers right above and below the current one,
+/// the previous one on the left and the next one on the right.
+pub fn draw_previews(draw: &mut Draw, state: &State) {
+    let params = state.board().params();
+    let Topology::Layered(depth) = params.topology else {
+        return;
+    };
+
+    let (grid_width, grid_height) = grid_size(params);
+    let preview_width = grid_width * PREVIEW_SCALE;
+    let layer = state.layer();
+
+    let previews = [
+        (layer.checked_sub(1), PREVIEW_MARGIN),
+        (
+            Some(layer + 1).filter(|&next| next < depth),
+            grid_width - preview_width - PREVIEW_MARGIN,
+        ),
+    ];
+
+    for (adjacent, left) in previews {
+        let Some(adjacent) = adjacent else {
+            continue;
+        };
+
+        let top = grid_height + PREVIEW_MARGIN;
+        let tile_size = TILE_SIZE * PREVIEW_SCALE;
+
+        draw.text(state.font_mono(), &format!("layer {}", adjacent + 1))
+            .color(Color::GRAY)
+            .size(14.)
+            .position(left, top - PREVIEW_MARGIN / 2.)
+            .v_align_middle();
+
+        for y in params.layer_rows(adjacent) {
+            let row = params.row_in_layer(y);
+            for x in 0..params.width {
+                let pos = (left + x as f32 * tile_size, top + row as f32 * tile_size);
+
+                draw.rect(pos, (tile_size, tile_size))
+                    .color(tile_color(state, x, y))
+                    .alpha(GHOST_ALPHA);
+            }
+        }
+    }
+}
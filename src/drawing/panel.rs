@@ -0,0 +1,300 @@
+use notan::prelude::*;
+
+use super::widget::{Rect, Ui};
+use super::{format_time, MineCounter, TILE_SIZE, UI_WIDTH, UNSURE_COLOR, WIN_COLOR};
+use crate::minefield::{FirstClick, Neighborhood, Topology, BEGINNER, EXPERT, INTERMEDIATE};
+use crate::puzzle::MAX_DIFFICULTY;
+use crate::state::animation::RevealSpeed;
+use crate::state::confirm::Restart;
+use crate::state::panel::{self, Action};
+use crate::state::{Mode, Stage, State};
+
+const BUTTON_HEIGHT: f32 = 28.;
+const TOGGLE_HEIGHT: f32 = 22.;
+const MARGIN: f32 = 15.;
+
+/// The side panel, as a [Ui] pass: drawing it, or finding out what the player did with it.
+pub fn show(ui: &mut Ui, state: &State) -> Option<Action> {
+    let mut action = None;
+    let mut button = |ui: &mut Ui, rect: Rect, key: KeyCode, label: &str, clicked: Action| {
+        if ui.button(rect, key, label, panel::is_enabled(state, clicked)) {
+            action = Some(clicked);
+        }
+    };
+
+    let time = match state.mode() {
+        Mode::Countdown { limit_milisec } => {
+            format_time(limit_milisec.saturating_sub(state.run_timer_milisec()))
+        }
+        Mode::Classic | Mode::Lives(_) | Mode::Survival | Mode::HotSeat => {
+            format_time(state.run_timer_milisec())
+        }
+    };
+
+    ui.label(&time, 30., Color::WHITE, row(1.));
+
+    button(
+        ui,
+        half_width(2., 0),
+        KeyCode::R,
+        "new",
+        Action::Restart(Restart::NewGame),
+    );
+    button(
+        ui,
+        half_width(2., 1),
+        KeyCode::L,
+        "retry",
+        Action::Restart(Restart::SameLayout),
+    );
+
+    let flags = state.board().flags();
+    let mines = state.board().mines();
+
+    let mine_counter = match state.mine_counter() {
+        MineCounter::FlagsOfMines => Some(format!("{:03} / {:03}", flags, mines)),
+        MineCounter::MinesRemaining => Some(format!("{:03}", mines as i64 - flags as i64)),
+        MineCounter::Hidden => None,
+    };
+
+    if let Some(mine_counter) = mine_counter {
+        ui.label(&mine_counter, 30., Color::WHITE, row(3.));
+    }
+
+    let reveal_speed = match state.reveal_speed() {
+        RevealSpeed::Instant => "reveal: instant",
+        RevealSpeed::Fast => "reveal: fast",
+        RevealSpeed::Slow => "reveal: slow",
+    };
+
+    ui.label(reveal_speed, 16., Color::GRAY, row(3.9));
+
+    let audio = state.audio();
+    let sound = if audio.is_muted() {
+        "sound: muted".to_owned()
+    } else {
+        format!("sound: {:.0}%", audio.volume() * 100.)
+    };
+
+    ui.label(&sound, 16., Color::GRAY, row(4.4));
+
+    let best = state
+        .best_time()
+        .map_or_else(|| "--:--.---".to_owned(), format_time);
+
+    ui.label(&format!("BEST {best}"), 20., Color::WHITE, row(5.));
+
+    let mode = match state.mode() {
+        Mode::Classic => "mode: classic".to_owned(),
+        Mode::Countdown { .. } => "mode: countdown".to_owned(),
+        Mode::Lives(lives) => format!("mode: lives, {} / {lives} left", state.lives_left()),
+        Mode::Survival => format!("mode: survival, {} cleared", state.boards_cleared()),
+        Mode::HotSeat => "mode: hot seat".to_owned(),
+    };
+
+    ui.label(&mode, 16., Color::GRAY, row(5.75));
+
+    let toggled = if state.mode() == Mode::HotSeat {
+        show_scoreboards(ui, state);
+        None
+    } else {
+        show_assists(ui, state)
+    };
+
+    let first_click = match state.board().params().first_click {
+        FirstClick::Safe => "first click: tile safe".to_owned(),
+        FirstClick::SafeArea => "first click: neighbors safe".to_owned(),
+        FirstClick::Opening(size) => format!("first click: opens {size}+"),
+    };
+
+    ui.label(&first_click, 16., Color::GRAY, row(10.));
+
+    let topology = match state.board().params().topology {
+        Topology::Bounded => "board: bounded".to_owned(),
+        Topology::Toroidal => "board: toroidal".to_owned(),
+        Topology::Hex => "board: hex".to_owned(),
+        Topology::Layered(depth) => format!("board: {depth} layers"),
+    };
+
+    ui.label(&topology, 16., Color::GRAY, row(10.6));
+
+    let neighborhood = match (
+        state.board().params().topology,
+        state.board().params().neighborhood,
+    ) {
+        (Topology::Hex, _) => "rule: hex sides (6)",
+        (Topology::Layered(_), _) => "rule: 3D cube (26)",
+        (_, Neighborhood::VonNeumann) => "rule: von Neumann (4)",
+        (_, Neighborhood::Moore) => "rule: Moore (8)",
+        (_, Neighborhood::Radius2) => "rule: radius 2 (24)",
+        (_, Neighborhood::Knight) => "rule: knight (8)",
+    };
+
+    ui.label(neighborhood, 16., Color::GRAY, row(11.2));
+
+    let mines_per_tile = format!(
+        "mines per tile: up to {}",
+        state.board().params().mines_per_tile
+    );
+
+    ui.label(&mines_per_tile, 16., Color::GRAY, row(11.8));
+
+    if let Topology::Layered(depth) = state.board().params().topology {
+        let layer = format!("layer {} / {depth} (PgUp/PgDn)", state.layer() + 1);
+        ui.label(&layer, 16., Color::WHITE, row(12.4));
+    }
+
+    if let Some(puzzle) = state.puzzle() {
+        let title = format!(
+            "puzzle: {} ({}/{MAX_DIFFICULTY})",
+            puzzle.title, puzzle.difficulty
+        );
+        ui.label(&title, 16., Color::WHITE, row(13.));
+    }
+
+    if let Some(run) = state.daily() {
+        let attempt = if run.official { "official" } else { "practice" };
+        let daily = format!("daily {}, {attempt}", run.daily.date());
+        ui.label(&daily, 16., Color::WHITE, row(13.6));
+
+        let share = match state.stage() {
+            Stage::Victory(_) | Stage::Defeat(_) if run.shared => Some("result exported"),
+            Stage::Victory(_) | Stage::Defeat(_) => Some("X: share result"),
+            _ => None,
+        };
+
+        if let Some(share) = share {
+            ui.label(share, 16., Color::WHITE, row(9.2));
+        }
+    }
+
+    let tag = match (state.is_assisted(), state.is_practice()) {
+        (true, true) => Some("ASSISTED PRACTICE"),
+        (true, false) => Some("ASSISTED"),
+        (false, true) => Some("PRACTICE"),
+        (false, false) => None,
+    };
+
+    if let Some(tag) = tag {
+        ui.label(tag, 20., UNSURE_COLOR, row(8.5));
+    }
+
+    button(
+        ui,
+        half_width(14.5, 0),
+        KeyCode::Escape,
+        "pause",
+        Action::Pause,
+    );
+    button(
+        ui,
+        half_width(14.5, 1),
+        KeyCode::O,
+        "settings",
+        Action::Settings,
+    );
+    button(ui, half_width(15.3, 0), KeyCode::H, "hint", Action::Hint);
+    button(ui, half_width(15.3, 1), KeyCode::U, "undo", Action::Undo);
+
+    let difficulty = match state.board().params() {
+        params if params == BEGINNER => "beginner",
+        params if params == INTERMEDIATE => "intermediate",
+        params if params == EXPERT => "expert",
+        _ => "custom",
+    };
+
+    button(
+        ui,
+        full_width(16.1),
+        KeyCode::Tab,
+        &format!("difficulty, {difficulty}"),
+        Action::Difficulty,
+    );
+
+    action.or(toggled)
+}
+
+/// Which assists are enabled, as toggles.
+fn show_assists(ui: &mut Ui, state: &State) -> Option<Action> {
+    let assists = state.assists();
+    let assist_toggles = [
+        (
+            assists.auto_flag,
+            KeyCode::Key1,
+            "auto-flag",
+            Action::AutoFlag,
+        ),
+        (
+            assists.auto_chord,
+            KeyCode::Key2,
+            "auto-chord",
+            Action::AutoChord,
+        ),
+        (
+            assists.auto_open,
+            KeyCode::Key3,
+            "auto-open",
+            Action::AutoOpen,
+        ),
+    ];
+
+    let mut toggled = None;
+    for (i, (on, key, label, action)) in assist_toggles.into_iter().enumerate() {
+        let (_, center_y) = row(6.5 + 0.6 * i as f32);
+        let rect = Rect::new(
+            (UI_WIDTH / 4., center_y - TOGGLE_HEIGHT / 2.),
+            (UI_WIDTH / 2., TOGGLE_HEIGHT),
+        );
+
+        if ui.toggle(rect, key, label, on, panel::is_enabled(state, action)) {
+            toggled = Some(action);
+        }
+    }
+
+    toggled
+}
+
+/// Score of each player of a hot-seat game, the one whose turn it is highlighted,
+/// and who won once the board is cleared.
+fn show_scoreboards(ui: &mut Ui, state: &State) {
+    for (player, score) in state.scores().into_iter().enumerate() {
+        let active = player == state.active_player() && matches!(state.stage(), Stage::Playing);
+        let marker = if active { '>' } else { ' ' };
+        let scoreboard = format!("{marker} player {}: {score:4}", player + 1);
+        let color = if active { Color::WHITE } else { Color::GRAY };
+
+        ui.label(&scoreboard, 16., color, row(6.5 + 0.6 * player as f32));
+    }
+
+    if let Stage::Victory(_) = state.stage() {
+        let result = match state.leading_player() {
+            Some(player) => format!("PLAYER {} WINS", player + 1),
+            None => "DRAW".to_owned(),
+        };
+
+        ui.label(&result, 20., WIN_COLOR, row(8.5));
+    }
+}
+
+/// Center of the panel at the given height, in tiles.
+fn row(tiles: f32) -> (f32, f32) {
+    (UI_WIDTH / 2., TILE_SIZE * tiles)
+}
+
+/// A button taking the left or right half of the panel, centered on the given height.
+fn half_width(tiles: f32, column: usize) -> Rect {
+    let width = UI_WIDTH / 2. - 2. * MARGIN;
+    let left = MARGIN + (UI_WIDTH / 2.) * column as f32;
+    Rect::new(
+        (left, TILE_SIZE * tiles - BUTTON_HEIGHT / 2.),
+        (width, BUTTON_HEIGHT),
+    )
+}
+
+/// A button taking the whole width of the panel, centered on the given height.
+fn full_width(tiles: f32) -> Rect {
+    Rect::new(
+        (MARGIN, TILE_SIZE * tiles - BUTTON_HEIGHT / 2.),
+        (UI_WIDTH - 2. * MARGIN, BUTTON_HEIGHT),
+    )
+}
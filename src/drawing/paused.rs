@@ -0,0 +1,98 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{board_origin, grid_size, MineCounter, COVER_COLOR, OUTLINE_COLOR, TILE_SIZE};
+use crate::minefield::Params;
+use crate::state::animation::RevealSpeed;
+use crate::state::paused::{Item, Page, PauseMenu};
+use crate::state::State;
+
+const ITEM_HEIGHT: f32 = TILE_SIZE;
+
+/// The pause menu, covering the whole board so nothing of it can be studied.
+pub fn draw(draw: &mut Draw, state: &State, menu: &PauseMenu) {
+    let params = state.board().params();
+    let size @ (width, _) = grid_size(params);
+
+    draw.rect((0., 0.), size).color(Color::BLACK);
+    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+
+    let items = menu.page.items();
+    let top = menu_top(params, items.len());
+
+    let title = match menu.page {
+        Page::Main => "PAUSED",
+        Page::Settings => "SETTINGS",
+    };
+
+    draw.text(state.font(), title)
+        .color(Color::WHITE)
+        .size(30.)
+        .position(width / 2., top + ITEM_HEIGHT / 2.)
+        .h_align_center()
+        .v_align_middle();
+
+    for (idx, &item) in items.iter().enumerate() {
+        let item_top = top + ITEM_HEIGHT * (idx + 1) as f32;
+
+        if idx == menu.selected {
+            draw.rect((0., item_top), (width, ITEM_HEIGHT))
+                .color(COVER_COLOR);
+        }
+
+        draw.text(state.font_mono(), &label(state, item))
+            .color(Color::WHITE)
+            .size(18.)
+            .position(width / 2., item_top + ITEM_HEIGHT / 2.)
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+/// Index of the menu item under the mouse, out of a page of `count` items.
+pub fn item_at(params: Params, count: usize, (mouse_x, mouse_y): (f32, f32)) -> Option<usize> {
+    let (origin_x, origin_y) = board_origin(params);
+    let (mouse_x, mouse_y) = (mouse_x - origin_x, mouse_y - origin_y);
+    let (width, _) = grid_size(params);
+
+    // the title takes the row above the first item
+    let first_top = menu_top(params, count) + ITEM_HEIGHT;
+    let row = f32::floor((mouse_y - first_top) / ITEM_HEIGHT);
+
+    let in_bounds = mouse_x >= 0. && mouse_x <= width && row >= 0. && row < count as f32;
+    in_bounds.then_some(row as usize)
+}
+
+/// Top of the title row, for the title and `count` items to be centered on the board.
+fn menu_top(params: Params, count: usize) -> f32 {
+    let (_, height) = grid_size(params);
+    f32::max(0., (height - ITEM_HEIGHT * (count + 1) as f32) / 2.)
+}
+
+fn label(state: &State, item: Item) -> String {
+    let audio = state.audio();
+
+    match item {
+        Item::Resume => "Resume".to_owned(),
+        Item::Restart => "Restart".to_owned(),
+        Item::Settings => "Settings".to_owned(),
+        Item::Abandon => "Abandon game".to_owned(),
+        Item::RevealSpeed => match state.reveal_speed() {
+            RevealSpeed::Instant => "Reveal: instant".to_owned(),
+            RevealSpeed::Fast => "Reveal: fast".to_owned(),
+            RevealSpeed::Slow => "Reveal: slow".to_owned(),
+        },
+        Item::Mute if audio.is_muted() => "Sound: off".to_owned(),
+        Item::Mute => "Sound: on".to_owned(),
+        Item::VolumeDown => format!("Volume - ({:.0}%)", audio.volume() * 100.),
+        Item::VolumeUp => format!("Volume + ({:.0}%)", audio.volume() * 100.),
+        Item::UnsureMarks if state.board().unsure_marks() => "Unsure marks: on".to_owned(),
+        Item::UnsureMarks => "Unsure marks: off".to_owned(),
+        Item::MineCounter => match state.mine_counter() {
+            MineCounter::FlagsOfMines => "Counter: flags / mines".to_owned(),
+            MineCounter::MinesRemaining => "Counter: mines left".to_owned(),
+            MineCounter::Hidden => "Counter: hidden".to_owned(),
+        },
+        Item::Back => "Back".to_owned(),
+    }
+}
@@ -0,0 +1,74 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{format_time, grid_size, COVER_COLOR, OUTLINE_COLOR, TILE_SIZE, WIN_COLOR};
+use crate::puzzle::MAX_DIFFICULTY;
+use crate::state::State;
+
+const ENTRY_HEIGHT: f32 = TILE_SIZE;
+
+/// List of the puzzles in the pack, scrolled to keep the selected one visible.
+pub fn draw(draw: &mut Draw, state: &State, selected: usize) {
+    let size @ (width, height) = grid_size(state.board().params());
+
+    draw.rect((0., 0.), size).color(Color::BLACK);
+    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+
+    draw.text(state.font(), "PUZZLES")
+        .color(Color::WHITE)
+        .size(30.)
+        .position(width / 2., ENTRY_HEIGHT / 2.)
+        .h_align_center()
+        .v_align_middle();
+
+    // the title takes the first row, and the help line the last one
+    let visible = ((height / ENTRY_HEIGHT) as usize).saturating_sub(2).max(1);
+    let first = selected.saturating_sub(visible - 1);
+
+    let puzzles = state.puzzles().iter().enumerate().skip(first).take(visible);
+    for (row, (idx, puzzle)) in puzzles.enumerate() {
+        let top = ENTRY_HEIGHT * (row + 1) as f32;
+        let center_y = top + ENTRY_HEIGHT / 2.;
+
+        if idx == selected {
+            draw.rect((0., top), (width, ENTRY_HEIGHT))
+                .color(COVER_COLOR);
+        }
+
+        let best = state.completions().best(puzzle);
+        let difficulty = "*".repeat(puzzle.difficulty as usize)
+            + &" ".repeat((MAX_DIFFICULTY - puzzle.difficulty) as usize);
+
+        draw.text(state.font_mono(), &difficulty)
+            .color(Color::WHITE)
+            .size(16.)
+            .position(TILE_SIZE / 2., center_y)
+            .v_align_middle();
+
+        draw.text(state.font(), &puzzle.title)
+            .color(if best.is_some() {
+                WIN_COLOR
+            } else {
+                Color::WHITE
+            })
+            .size(20.)
+            .position(TILE_SIZE * 2.5, center_y)
+            .v_align_middle();
+
+        if let Some(best) = best {
+            draw.text(state.font_mono(), &format_time(best))
+                .color(WIN_COLOR)
+                .size(16.)
+                .position(width - TILE_SIZE / 2., center_y)
+                .h_align_right()
+                .v_align_middle();
+        }
+    }
+
+    draw.text(state.font_mono(), "RETURN: play  ESC: back to classic")
+        .color(Color::GRAY)
+        .size(14.)
+        .position(width / 2., height - ENTRY_HEIGHT / 2.)
+        .h_align_center()
+        .v_align_middle();
+}
@@ -0,0 +1,101 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{
+    draw_tile_shape, format_time, gauss, grid_size, tile_center, FLAG_COLOR, OUTLINE_COLOR,
+    WIN_COLOR,
+};
+use crate::state::animation::{Effect, POP_MILISEC};
+use crate::state::victory::VictoryState;
+use crate::state::State;
+
+/// Size a popped-in flag settles at, relative to its tile.
+const FLAG_SCALE: f32 = 0.6;
+
+const LINE_HEIGHT: f32 = 26.;
+
+/// Flags popping in over the mines left on the current layer.
+pub fn draw_celebration(draw: &mut Draw, state: &State) {
+    let params = state.board().params();
+    let animations = state.animations();
+
+    for flag in animations.with_effect(Effect::Celebration) {
+        if params.layer_of(flag.pos.1) != state.layer() {
+            continue;
+        }
+
+        let Some(elapsed) = animations.elapsed_milisec(flag) else {
+            continue;
+        };
+
+        // grows a bit past its final size, then shrinks back into place
+        let progress = elapsed as f32 / POP_MILISEC as f32;
+        let scale = FLAG_SCALE * f32::min(progress, 1.) + gauss(progress, 0.25, 1., 0.3);
+
+        let center = tile_center(params, flag.pos.0, flag.pos.1);
+        draw_tile_shape(draw, params.topology, center, scale, FLAG_COLOR, None);
+    }
+}
+
+/// Summary of the won game over the board, once every flag popped in.
+pub fn draw_summary(draw: &mut Draw, state: &State, victory: &VictoryState) {
+    if !state.animations().settled(Effect::Celebration, POP_MILISEC) {
+        return;
+    }
+
+    let size @ (width, height) = grid_size(state.board().params());
+
+    draw.rect((0., 0.), size).color(Color::BLACK).alpha(0.75);
+    draw.rect((0., 0.), size).color(OUTLINE_COLOR).stroke(3.);
+
+    let mut lines = vec![
+        (format_time(victory.time_milisec), Color::WHITE),
+        (
+            format!(
+                "3BV {}, {:.2} 3BV/s",
+                victory.three_bv,
+                victory.three_bv_per_second()
+            ),
+            Color::WHITE,
+        ),
+        (
+            format!(
+                "{} clicks, {:.0}% efficiency",
+                victory.clicks,
+                victory.efficiency()
+            ),
+            Color::WHITE,
+        ),
+    ];
+
+    if victory.personal_best {
+        lines.push(("NEW PERSONAL BEST".to_owned(), WIN_COLOR));
+    }
+
+    lines.push((String::new(), Color::WHITE));
+    lines.push(("Space: new game".to_owned(), Color::GRAY));
+    lines.push(("1 / 2 / 3: difficulty".to_owned(), Color::GRAY));
+    lines.push(("S: save replay".to_owned(), Color::GRAY));
+
+    if let Some(status) = &victory.save_status {
+        lines.push((status.clone(), Color::GRAY));
+    }
+
+    let title_y = height / 2. - LINE_HEIGHT * (lines.len() as f32 + 2.) / 2.;
+
+    draw.text(state.font(), "VICTORY")
+        .color(WIN_COLOR)
+        .size(36.)
+        .position(width / 2., title_y)
+        .h_align_center()
+        .v_align_middle();
+
+    for (i, (line, color)) in lines.iter().enumerate() {
+        draw.text(state.font_mono(), line)
+            .color(*color)
+            .size(16.)
+            .position(width / 2., title_y + LINE_HEIGHT * (i as f32 + 2.))
+            .h_align_center()
+            .v_align_middle();
+    }
+}
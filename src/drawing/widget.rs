@@ -0,0 +1,183 @@
+use notan::draw::*;
+use notan::prelude::*;
+
+use super::{COVER_COLOR, OUTLINE_COLOR};
+
+const BUTTON_STROKE: f32 = 2.;
+const WIDGET_TEXT_SIZE: f32 = 14.;
+
+/// Area taken by a widget, relative to the origin of its [Ui].
+#[derive(Debug, Clone, Copy)]
+pub struct Rect {
+    pub pos: (f32, f32),
+    pub size: (f32, f32),
+}
+
+/// What going through the widgets does with them.
+enum Pass<'a> {
+    /// Reacting to the keyboard and the mouse, from the update.
+    Input(&'a App),
+    /// Drawing them, from the draw.
+    Render(&'a mut Draw),
+}
+
+/// Immediate-mode widgets: the same code laying them out makes them react to input
+/// in the update, and draws them in the draw.
+///
+/// Every widget is a plain method call, returning whether it was used this frame.
+pub struct Ui<'a> {
+    pass: Pass<'a>,
+    /// Font of the text of every widget.
+    font: &'a Font,
+    /// Mouse position relative to the origin of the widgets.
+    mouse: (f32, f32),
+}
+
+impl Rect {
+    pub fn new(pos: (f32, f32), size: (f32, f32)) -> Self {
+        Self { pos, size }
+    }
+
+    fn contains(self, (x, y): (f32, f32)) -> bool {
+        let (left, top) = self.pos;
+        let (width, height) = self.size;
+        x >= left && x <= left + width && y >= top && y <= top + height
+    }
+}
+
+impl<'a> Ui<'a> {
+    /// Widgets reacting to the input of the frame, laid out from `origin` on the screen.
+    pub fn input(app: &'a App, font: &'a Font, origin: (f32, f32)) -> Self {
+        let (mouse_x, mouse_y) = app.mouse.position();
+        Self {
+            pass: Pass::Input(app),
+            font,
+            mouse: (mouse_x - origin.0, mouse_y - origin.1),
+        }
+    }
+
+    /// Widgets drawn with `draw`, already transformed to their `origin` on the screen.
+    pub fn render(
+        draw: &'a mut Draw,
+        font: &'a Font,
+        mouse: (f32, f32),
+        origin: (f32, f32),
+    ) -> Self {
+        Self {
+            pass: Pass::Render(draw),
+            font,
+            mouse: (mouse.0 - origin.0, mouse.1 - origin.1),
+        }
+    }
+
+    /// Text centered on the given point, only ever drawn.
+    pub fn label(&mut self, text: &str, size: f32, color: Color, center: (f32, f32)) {
+        let font = self.font;
+        let Pass::Render(draw) = &mut self.pass else {
+            return;
+        };
+
+        draw.text(font, text)
+            .color(color)
+            .size(size)
+            .position(center.0, center.1)
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    /// A button labelled with its keyboard shortcut, pressed by clicking it or with that key.
+    ///
+    /// Disabled buttons are grayed out, and never pressed.
+    pub fn button(&mut self, rect: Rect, key: KeyCode, label: &str, enabled: bool) -> bool {
+        let hovered = enabled && rect.contains(self.mouse);
+        let text = format!("{}: {label}", key_name(key));
+        let font = self.font;
+
+        let draw = match &mut self.pass {
+            Pass::Input(app) => return enabled && used(app, key, hovered),
+            Pass::Render(draw) => draw,
+        };
+
+        if hovered {
+            draw.rect(rect.pos, rect.size).color(COVER_COLOR);
+        }
+
+        let color = if enabled { Color::WHITE } else { Color::GRAY };
+        draw.rect(rect.pos, rect.size)
+            .color(if enabled { OUTLINE_COLOR } else { Color::GRAY })
+            .stroke(BUTTON_STROKE);
+
+        let (x, y) = rect.pos;
+        let (width, height) = rect.size;
+        draw.text(font, &text)
+            .color(color)
+            .size(WIDGET_TEXT_SIZE)
+            .position(x + width / 2., y + height / 2.)
+            .h_align_center()
+            .v_align_middle();
+
+        false
+    }
+
+    /// An option turned on and off by clicking it or with its key, with a box filled while on.
+    ///
+    /// Returns whether it was flipped, disabled toggles never are.
+    pub fn toggle(
+        &mut self,
+        rect: Rect,
+        key: KeyCode,
+        label: &str,
+        on: bool,
+        enabled: bool,
+    ) -> bool {
+        let hovered = enabled && rect.contains(self.mouse);
+        let text = format!("{}: {label}", key_name(key));
+        let font = self.font;
+
+        let draw = match &mut self.pass {
+            Pass::Input(app) => return enabled && used(app, key, hovered),
+            Pass::Render(draw) => draw,
+        };
+
+        if hovered {
+            draw.rect(rect.pos, rect.size).color(COVER_COLOR);
+        }
+
+        let (x, y) = rect.pos;
+        let (_, height) = rect.size;
+        let box_size = height / 2.;
+        let box_pos = (x + box_size / 2., y + box_size / 2.);
+
+        // filled while on, only outlined while off
+        {
+            let mut check_box = draw.rect(box_pos, (box_size, box_size));
+            check_box.color(OUTLINE_COLOR);
+            if !on {
+                check_box.stroke(BUTTON_STROKE);
+            }
+        }
+
+        draw.text(font, &text)
+            .color(if on { Color::WHITE } else { Color::GRAY })
+            .size(WIDGET_TEXT_SIZE)
+            .position(x + box_size * 2., y + height / 2.)
+            .v_align_middle();
+
+        false
+    }
+}
+
+fn used(app: &App, key: KeyCode, hovered: bool) -> bool {
+    app.keyboard.was_pressed(key) || (hovered && app.mouse.left_was_pressed())
+}
+
+/// How a key is spelled out in the widget labels.
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Escape => "Esc".to_owned(),
+        KeyCode::Key1 => "1".to_owned(),
+        KeyCode::Key2 => "2".to_owned(),
+        KeyCode::Key3 => "3".to_owned(),
+        _ => format!("{key:?}"),
+    }
+}
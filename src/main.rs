@@ -1,23 +1,27 @@
 #![allow(clippy::main_recursion)]
 
+pub(crate) mod audio;
+pub(crate) mod daily;
 pub(crate) mod drawing;
 pub(crate) mod minefield;
+pub(crate) mod puzzle;
 pub(crate) mod random;
+pub(crate) mod replay;
 pub(crate) mod state;
+pub(crate) mod stats;
 
 use notan::draw::*;
 use notan::prelude::*;
 
-use drawing::UI_WIDTH;
-
 #[notan_main]
 fn main() -> Result<(), String> {
     let difficulty = minefield::EXPERT;
-    let (width, height) = drawing::board_dims(difficulty);
+    let (width, height) = drawing::window_size(difficulty);
     let win = WindowConfig::default()
         .title("Enimdnal")
-        .size(width as i32 + UI_WIDTH as i32, height as _);
+        .size(width as _, height as _);
     notan::init_with(state::setup)
+        .event(state::event)
         .update(state::update)
         .draw(drawing::draw)
         .add_config(win)
@@ -1,35 +1,139 @@
+mod assist;
+mod endless;
+mod flood;
+mod outcome;
+mod solver;
+mod topology;
+
 use std::collections::HashSet;
+use std::fmt;
+use std::ops::Range;
 
 use crate::random::IteratorRandom;
 
+pub use assist::Assists;
+pub use endless::{EndlessBoard, CHUNK_SIZE};
+use flood::{flood, Spill};
+pub use outcome::{ActionOutcome, MarkChange, Reveal, Terminal};
+pub use solver::Hint;
+use topology::{stack_row, unstack_row};
+pub use topology::{Neighborhood, Topology};
+
 pub const BEGINNER: Params = Params {
     width: 8,
     height: 8,
     mines: 10,
+    first_click: FirstClick::SafeArea,
+    topology: Topology::Bounded,
+    neighborhood: Neighborhood::Moore,
+    mines_per_tile: 1,
 };
 pub const INTERMEDIATE: Params = Params {
     width: 16,
     height: 16,
     mines: 40,
+    first_click: FirstClick::SafeArea,
+    topology: Topology::Bounded,
+    neighborhood: Neighborhood::Moore,
+    mines_per_tile: 1,
 };
 pub const EXPERT: Params = Params {
     width: 30,
     height: 16,
     mines: 99,
+    first_click: FirstClick::SafeArea,
+    topology: Topology::Bounded,
+    neighborhood: Neighborhood::Moore,
+    mines_per_tile: 1,
 };
 
-#[derive(Debug, Clone, Copy)]
+/// Upper bound on layout rerolls when looking for a [FirstClick::Opening].
+const OPENING_ATTEMPTS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Params {
     pub width: usize,
     pub height: usize,
     pub mines: usize,
+    pub first_click: FirstClick,
+    pub topology: Topology,
+    pub neighborhood: Neighborhood,
+
+    /// Most mines a single tile can hold, 1 for the classic game.
+    ///
+    /// `mines` always counts individual mines, not mined tiles.
+    pub mines_per_tile: u8,
+}
+
+impl Params {
+    /// Dimensions of the board in tiles, with the layers of a
+    /// [Topology::Layered] board stacked vertically.
+    pub fn dims(&self) -> (usize, usize) {
+        (
+            self.width,
+            stack_row(self.height, self.topology.layers(), 0),
+        )
+    }
+
+    /// Layer of a [Topology::Layered] board the given board row is on, always 0 on flat boards.
+    pub fn layer_of(&self, y: usize) -> usize {
+        unstack_row(self.height, y).0
+    }
+
+    /// Row within its layer of the given board row, the row itself on flat boards.
+    pub fn row_in_layer(&self, y: usize) -> usize {
+        unstack_row(self.height, y).1
+    }
+
+    /// Board row of the given row within a layer.
+    pub fn stacked_row(&self, layer: usize, row: usize) -> usize {
+        stack_row(self.height, layer, row)
+    }
+
+    /// Board rows making up the given layer, every row on flat boards.
+    pub fn layer_rows(&self, layer: usize) -> Range<usize> {
+        self.stacked_row(layer, 0)..self.stacked_row(layer + 1, 0)
+    }
+
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.topology
+            .neighbors(self.neighborhood, self.dims(), (x, y))
+    }
+
+    /// Reject parameters no board can be laid out with.
+    fn check(&self) {
+        assert!(
+            self.topology.layers() > 0,
+            "a layered board needs at least one layer"
+        );
+    }
+}
+
+/// What the very first primary action on a board is guaranteed to open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirstClick {
+    /// Only the clicked tile is mine-free, like in the classic Windows game.
+    Safe,
+
+    /// The clicked tile and its neighbors are mine-free,
+    /// so the first uncovered tile is always a blank.
+    SafeArea,
+
+    /// Like [FirstClick::SafeArea], and the flood fill started by the first click
+    /// uncovers at least this many tiles.
+    ///
+    /// Layouts are rerolled until one qualifies. If the board is too dense
+    /// to find one in a reasonable number of attempts, the last layout is kept.
+    Opening(usize),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Mark {
     /// Mine flag, indicates 100% player certainty of a mine,
     /// and disables uncovering the marked field, for safety.
-    Flag,
+    ///
+    /// Carries the number of mines the player believes are in the tile.
+    Flag(u8),
 
     /// "Danger, probably" marker, for fields that are sorta suspicious,
     /// but not yet worthy of The [Mark::Flag].
@@ -45,7 +149,10 @@ pub enum Cover {
 
 #[derive(Debug, Clone, Copy)]
 pub enum Object {
-    Mine,
+    /// One or more mines, up to [Params::mines_per_tile].
+    Mine(u8),
+    /// Number of mines among the neighbors,
+    /// up to the size of the [Neighborhood] times [Params::mines_per_tile].
     Hint(u8),
     Blank,
 }
@@ -56,24 +163,71 @@ pub struct Tile {
     object: Object,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Board {
     tiles: Vec<Tile>,
     covered: usize,
     flags: usize,
+    mined_tiles: usize,
     params: Params,
     placed: bool,
+    /// Tile the mines were placed around, `None` for layouts placed as a whole.
+    first_action: Option<(usize, usize)>,
+    /// Seed of the mine layouts, `None` for fresh randomness every game.
+    seed: Option<u64>,
     defeat: bool,
+    /// Mined tiles uncovered without ending the game, see [Board::survive].
+    exploded: usize,
+    unsure_marks: bool,
 }
 
 impl Mark {
-    fn cycle(&mut self) {
-        *self = match self {
-            Self::None => Self::Flag,
-            Self::Flag => Self::Unsure,
-            Self::Unsure => Self::None,
+    /// Advance to the next mark in the cycle.
+    ///
+    /// Flags count up from 1 to `max_flag` before moving on.
+    /// With `unsure` disabled, the [Mark::Unsure] step is skipped
+    /// and the mark goes from the last flag straight back to [Mark::None].
+    fn cycle(&mut self, unsure: bool, max_flag: u8) {
+        *self = match *self {
+            Self::None => Self::Flag(1),
+            Self::Flag(n) if n < max_flag => Self::Flag(n + 1),
+            Self::Flag(_) if unsure => Self::Unsure,
+            Self::Flag(_) | Self::Unsure => Self::None,
         };
     }
+
+    /// Number of mines flagged by this mark.
+    fn flags(self) -> usize {
+        match self {
+            Self::Flag(n) => n as _,
+            Self::Unsure | Self::None => 0,
+        }
+    }
+}
+
+impl FirstClick {
+    /// The policy written as by its [Display](fmt::Display) implementation.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "safe" => Some(Self::Safe),
+            "safe-area" => Some(Self::SafeArea),
+            _ => text
+                .strip_prefix("opening ")?
+                .parse()
+                .ok()
+                .map(Self::Opening),
+        }
+    }
+}
+
+impl fmt::Display for FirstClick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Safe => write!(f, "safe"),
+            Self::SafeArea => write!(f, "safe-area"),
+            Self::Opening(size) => write!(f, "opening {size}"),
+        }
+    }
 }
 
 impl Tile {
@@ -93,11 +247,19 @@ impl Tile {
     }
 
     pub fn is_uncoverable(&self) -> bool {
-        matches!(self.cover, Cover::Up(mark) if mark != Mark::Flag)
+        matches!(self.cover, Cover::Up(_)) && !self.is_flag()
     }
 
     pub fn is_mine(&self) -> bool {
-        matches!(self.object, Object::Mine)
+        matches!(self.object, Object::Mine(_))
+    }
+
+    /// Number of mines in this tile.
+    pub fn mines(&self) -> usize {
+        match self.object {
+            Object::Mine(n) => n as _,
+            _ => 0,
+        }
     }
 
     pub fn is_hint(&self) -> bool {
@@ -109,19 +271,45 @@ impl Tile {
     }
 
     pub fn is_flag(&self) -> bool {
-        matches!(self.cover, Cover::Up(Mark::Flag))
+        matches!(self.cover, Cover::Up(Mark::Flag(_)))
+    }
+
+    /// Number of mines flagged on this tile.
+    pub fn flags(&self) -> usize {
+        match self.cover {
+            Cover::Up(mark) => mark.flags(),
+            Cover::Down => 0,
+        }
+    }
+
+    /// Mines the player knows about, either flagged or exploded.
+    pub fn known_mines(&self) -> usize {
+        match self.cover {
+            Cover::Up(mark) => mark.flags(),
+            Cover::Down => self.mines(),
+        }
     }
 }
 
 impl Board {
+    /// A fresh board for the given parameters.
+    ///
+    /// Panics on a [Topology::Layered] board without any layer.
     pub fn new(params: Params) -> Self {
-        let size = params.width * params.height;
+        params.check();
+        let (width, height) = params.dims();
+        let size = width * height;
         Self {
             tiles: vec![Tile::new(); size],
             covered: size,
             flags: 0,
+            mined_tiles: 0,
             placed: false,
+            first_action: None,
+            seed: None,
+            exploded: 0,
             defeat: false,
+            unsure_marks: true,
             params,
         }
     }
@@ -138,8 +326,12 @@ impl Board {
         Self::new(EXPERT)
     }
 
+    pub fn params(&self) -> Params {
+        self.params
+    }
+
     pub fn dims(&self) -> (usize, usize) {
-        (self.params.width, self.params.height)
+        self.params.dims()
     }
 
     pub fn mines(&self) -> usize {
@@ -156,17 +348,42 @@ impl Board {
     }
 
     pub fn is_victory(&self) -> bool {
-        self.covered == self.params.mines
+        self.covered + self.exploded == self.mined_tiles
     }
 
     pub fn is_defeat(&self) -> bool {
         self.defeat
     }
 
+    /// Carry on playing after uncovering a mine.
+    ///
+    /// The mine stays uncovered, counts as known for exploring around hints,
+    /// and no longer needs uncovering for a victory.
+    pub fn survive(&mut self) {
+        self.defeat = false;
+    }
+
     pub fn is_initialized(&self) -> bool {
         self.placed
     }
 
+    /// Tile the mines were placed around, `None` before they are placed or for a puzzle layout.
+    pub fn first_action(&self) -> Option<(usize, usize)> {
+        self.first_action
+    }
+
+    pub fn unsure_marks(&self) -> bool {
+        self.unsure_marks
+    }
+
+    /// Rule option for the secondary action.
+    ///
+    /// When disabled, marks cycle only through [Mark::None] and [Mark::Flag].
+    /// Tiles already marked [Mark::Unsure] go back to [Mark::None] on their next cycle.
+    pub fn set_unsure_marks(&mut self, enabled: bool) {
+        self.unsure_marks = enabled;
+    }
+
     /// Primary interface for acting on a minefield.
     ///
     /// Corresponds to one of the primary actions on a tile:
@@ -176,138 +393,324 @@ impl Board {
     ///
     /// Uncovering every non-mine tile is the win condition.
     /// Note that the mine tiles are **not** required to be flagged (looking at you, speedrunners).
-    pub fn handle_primary_action(&mut self, x: usize, y: usize) {
-        if !self.placed {
+    pub fn handle_primary_action(&mut self, x: usize, y: usize) -> ActionOutcome {
+        let initialized = !self.placed;
+        if initialized {
             self.place_mines_and_hints(x, y);
             self.placed = true;
+            self.first_action = Some((x, y));
         }
 
         let tile_idx = self.coords_to_index(x, y);
-        let tile = &mut self.tiles[tile_idx];
+        let tile = self.tiles[tile_idx];
 
-        if let (Cover::Down, Object::Hint(hint)) = (tile.cover, tile.object) {
-            self.explore_around(hint, x, y);
-            return;
-        }
+        let revealed = if let (Cover::Down, Object::Hint(hint)) = (tile.cover, tile.object) {
+            self.explore_around(hint, x, y)
+        } else if tile.is_uncoverable() {
+            self.uncover(x, y, 0)
+        } else {
+            vec![]
+        };
 
-        if !tile.is_uncoverable() {
-            return;
-        }
+        self.check_invariants();
 
-        self.uncover(x, y);
+        ActionOutcome {
+            terminal: self.terminal(&revealed),
+            revealed,
+            marks: vec![],
+            initialized,
+        }
     }
 
     /// Primary interface for acting on a minefield.
     ///
     /// Corresponds to the action of cycling through
     /// available covered-tile marks (the [Mark] type).
-    pub fn handle_secondary_action(&mut self, x: usize, y: usize) {
+    pub fn handle_secondary_action(&mut self, x: usize, y: usize) -> ActionOutcome {
         let tile_idx = self.coords_to_index(x, y);
         let Cover::Up(mark) = &mut self.tiles[tile_idx].cover else {
-            return;
+            return ActionOutcome::default();
         };
 
-        mark.cycle();
-        match mark {
-            Mark::Flag => self.flags += 1,
-            Mark::Unsure => self.flags -= 1,
-            _ => (),
+        let before = *mark;
+        mark.cycle(self.unsure_marks, self.params.mines_per_tile);
+        let after = *mark;
+        self.flags = self.flags + after.flags() - before.flags();
+
+        self.check_invariants();
+
+        ActionOutcome {
+            marks: vec![MarkChange {
+                pos: (x, y),
+                before,
+                after,
+            }],
+            ..ActionOutcome::default()
         }
     }
 
+    /// Start over with different parameters, keeping the rule options.
+    ///
+    /// Panics on a [Topology::Layered] board without any layer.
+    pub fn set_params(&mut self, params: Params) {
+        params.check();
+        self.params = params;
+        let (width, height) = params.dims();
+        self.tiles = vec![Tile::new(); width * height];
+        self.reset();
+    }
+
     pub fn reset(&mut self) {
         self.tiles.fill(Tile::new());
         self.placed = false;
+        self.first_action = None;
         self.defeat = false;
+        self.exploded = 0;
         self.covered = self.tiles.len();
         self.flags = 0;
+        self.mined_tiles = 0;
     }
 
-    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
-        let offsets = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
+    /// Cover every tile again for another attempt at the same mine layout,
+    /// opening with the same first action so that it is just as safe.
+    ///
+    /// Returns what that first action uncovered, as [Board::handle_primary_action] does.
+    /// Before the mines are placed there is no layout to keep, and nothing changes.
+    pub fn retry(&mut self) -> ActionOutcome {
+        if !self.placed {
+            return ActionOutcome::default();
+        }
+
+        for tile in &mut self.tiles {
+            tile.cover = Cover::Up(Mark::None);
+        }
+        self.defeat = false;
+        self.exploded = 0;
+        self.covered = self.tiles.len();
+        self.flags = 0;
+
+        self.check_invariants();
+
+        match self.first_action {
+            Some((x, y)) => self.handle_primary_action(x, y),
+            None => ActionOutcome::default(),
+        }
+    }
+
+    /// Draw every following mine layout from the given seed,
+    /// so that the same first action always yields the same board.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    /// Least number of primary actions clearing the board, known as its 3BV.
+    ///
+    /// Every opening takes one action, and so does every hint tile
+    /// no opening reaches. Only meaningful once the mines are placed.
+    pub fn three_bv(&self) -> usize {
+        let (width, height) = self.dims();
+        let mut reached = HashSet::new();
+        let mut clicks = 0;
 
-        // copy these values to avoid borrowing `self` in the closure
-        let width = self.params.width;
-        let height = self.params.height;
-        offsets.into_iter().filter_map(move |(off_x, off_y)| {
-            let new_x = x as i32 + off_x;
-            let new_y = y as i32 + off_y;
-            let x_in_bounds = new_x >= 0 && new_x < width as i32;
-            let y_in_bounds = new_y >= 0 && new_y < height as i32;
-
-            if !x_in_bounds || !y_in_bounds {
-                return None;
+        for y in 0..height {
+            for x in 0..width {
+                if !self.tile(x, y).is_blank() || reached.contains(&(x, y)) {
+                    continue;
+                }
+
+                clicks += 1;
+
+                let neighbors = |(x, y)| self.neighbors(x, y);
+                flood((x, y), neighbors, |pos @ (current_x, current_y), _| {
+                    reached.insert(pos);
+                    if self.tile(current_x, current_y).is_blank() {
+                        Spill::Spread
+                    } else {
+                        Spill::Stop
+                    }
+                });
             }
+        }
 
-            Some((new_x as _, new_y as _))
-        })
+        let unreached_hints = self
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|&(idx, tile)| {
+                let pos = (idx % width, idx / width);
+                tile.is_hint() && !reached.contains(&pos)
+            })
+            .count();
+
+        clicks + unreached_hints
+    }
+
+    /// Start over on a predetermined mine layout, instead of a random one
+    /// placed on the first primary action.
+    ///
+    /// `mines` lists the coordinates of every mine, a tile listed several times
+    /// holds several mines. [Params::mines] should match the length of the list.
+    pub fn place_layout(&mut self, mines: &[(usize, usize)]) {
+        self.reset();
+
+        for &(x, y) in mines {
+            let idx = self.coords_to_index(x, y);
+            self.add_mine(idx);
+        }
+
+        self.mined_tiles = self.tiles.iter().filter(|tile| tile.is_mine()).count();
+        self.place_hints();
+        self.placed = true;
+    }
+
+    fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.params.neighbors(x, y)
     }
 
     /// A flood-fill-style uncovering procedure,
     /// where the uncovering "spills" over a surrounding area
     /// bounded by hint tiles (inclusive).
     ///
-    /// Algorithmically, this is equivalent to a DFS/BFS traversal
-    /// starting from a player-uncovered tile
+    /// Algorithmically, this is a BFS traversal
+    /// starting from a player-uncovered tile at the given `depth`
     /// and stopping on already uncovered tiles and hint tiles.
-    fn flood_uncover(&mut self, x: usize, y: usize) {
-        let mut flooded: Vec<_> = self.neighbors(x, y).collect();
-        let mut visited = HashSet::new();
+    ///
+    /// Returns the tiles uncovered on the way, nearest first.
+    fn flood_uncover(&mut self, x: usize, y: usize, depth: usize) -> Vec<Reveal> {
+        let params = self.params;
+        let mut revealed = vec![];
 
-        while let Some((current_x, current_y)) = flooded.pop() {
-            let t_idx = self.coords_to_index(current_x, current_y);
-            let tile = &mut self.tiles[t_idx];
+        flood(
+            (x, y),
+            |(x, y)| params.neighbors(x, y),
+            |pos, distance| {
+                // the tile the flood starts from is already uncovered
+                if pos == (x, y) {
+                    return Spill::Spread;
+                }
 
-            if !tile.is_uncoverable() || !visited.insert((current_x, current_y)) {
-                continue;
-            }
+                let t_idx = self.coords_to_index(pos.0, pos.1);
+                if !self.tiles[t_idx].is_uncoverable() {
+                    return Spill::Blocked;
+                }
 
-            tile.cover = Cover::Down;
-            self.covered -= 1;
+                self.uncover_tile(t_idx);
+                revealed.push(Reveal {
+                    pos,
+                    object: self.tiles[t_idx].object,
+                    depth: depth + distance,
+                });
 
-            if tile.is_blank() {
-                let n = self.neighbors(current_x, current_y);
-                flooded.extend(n);
-            }
-        }
+                if self.tiles[t_idx].is_blank() {
+                    Spill::Spread
+                } else {
+                    Spill::Stop
+                }
+            },
+        );
+
+        revealed
     }
 
-    fn uncover(&mut self, x: usize, y: usize) {
+    /// Uncover a covered tile, along with whatever it floods into.
+    fn uncover(&mut self, x: usize, y: usize, depth: usize) -> Vec<Reveal> {
         let tile_idx = self.coords_to_index(x, y);
-        let tile = &mut self.tiles[tile_idx];
+        self.uncover_tile(tile_idx);
+
+        let object = self.tiles[tile_idx].object;
+        let mut revealed = vec![Reveal {
+            pos: (x, y),
+            object,
+            depth,
+        }];
+
+        match object {
+            Object::Mine(_) => self.defeat = true,
+            Object::Blank => revealed.extend(self.flood_uncover(x, y, depth)),
+            Object::Hint(_) => (),
+        }
+
+        revealed
+    }
+
+    /// How the game ended after uncovering the given tiles, if it did.
+    fn terminal(&self, revealed: &[Reveal]) -> Option<Terminal> {
+        let mine = revealed
+            .iter()
+            .find(|reveal| matches!(reveal.object, Object::Mine(_)));
+
+        match mine {
+            Some(reveal) if self.defeat => Some(Terminal::Defeat {
+                triggered: reveal.pos,
+            }),
+            _ if self.is_victory() => Some(Terminal::Victory),
+            _ => None,
+        }
+    }
+
+    /// The single place where a tile gets uncovered,
+    /// so that the `covered`, `flags` and `exploded` counters always follow the tiles.
+    fn uncover_tile(&mut self, idx: usize) {
+        let tile = &mut self.tiles[idx];
+        let Cover::Up(mark) = tile.cover else {
+            return;
+        };
 
+        self.flags -= mark.flags();
         tile.cover = Cover::Down;
         self.covered -= 1;
 
-        match tile.object {
-            Object::Mine => self.defeat = true,
-            Object::Blank => self.flood_uncover(x, y),
-            Object::Hint(_) => (),
+        if tile.is_mine() {
+            self.exploded += 1;
         }
     }
 
+    /// Debug-build check that the cached counters agree with the tiles.
+    ///
+    /// Runs after every player action, so a counter drifting out of sync
+    /// is caught at the action that caused it rather than on the victory screen.
+    fn check_invariants(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        let flags: usize = self.tiles.iter().map(Tile::flags).sum();
+        let covered = self
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile.cover, Cover::Up(_)))
+            .count();
+
+        let exploded = self
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile.cover, Cover::Down) && tile.is_mine())
+            .count();
+
+        debug_assert_eq!(self.flags, flags, "flag counter out of sync with tiles");
+        debug_assert_eq!(
+            self.covered, covered,
+            "covered counter out of sync with tiles"
+        );
+        debug_assert_eq!(
+            self.exploded, exploded,
+            "exploded counter out of sync with tiles"
+        );
+    }
+
     /// Clicking on a hint tile if there are exactly as many flags around it as hinted
     /// causes the remaining covered tiles to be uncovered automatically.
     ///
     /// Beware: if the flags are misplaced, this is an instant defeat!
-    fn explore_around(&mut self, hinted: u8, x: usize, y: usize) {
+    fn explore_around(&mut self, hinted: u8, x: usize, y: usize) -> Vec<Reveal> {
         let neighbors: Vec<_> = self.neighbors(x, y).collect();
-        let n_flags = neighbors
+        let n_flags: usize = neighbors
             .iter()
-            .filter(|&&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].is_flag())
-            .count();
+            .map(|&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].known_mines())
+            .sum();
 
         if hinted as usize != n_flags {
-            return;
+            return vec![];
         }
 
         let explored: Vec<_> = neighbors
@@ -316,9 +719,15 @@ impl Board {
             .filter(|&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].is_uncoverable())
             .collect();
 
+        let mut revealed = vec![];
         for (current_x, current_y) in explored {
-            self.uncover(current_x, current_y);
+            // an earlier flood may have got there first
+            if self.tiles[self.coords_to_index(current_x, current_y)].is_uncoverable() {
+                revealed.extend(self.uncover(current_x, current_y, 1));
+            }
         }
+
+        revealed
     }
 
     /// Get single dimension index of 2D tile in tiles array
@@ -327,45 +736,307 @@ impl Board {
     }
 
     fn place_mines_and_hints(&mut self, x: usize, y: usize) {
-        let skip: Vec<_> = self
-            .neighbors(x, y)
-            .chain([(x, y)])
-            .map(|(xx, yy)| self.coords_to_index(xx, yy))
-            .collect();
-        self.place_mines(&skip);
-        self.place_hints();
+        let tile_idx = self.coords_to_index(x, y);
+        let skip: Vec<_> = match self.params.first_click {
+            FirstClick::Safe => vec![tile_idx],
+            FirstClick::SafeArea | FirstClick::Opening(_) => self
+                .neighbors(x, y)
+                .map(|(xx, yy)| self.coords_to_index(xx, yy))
+                .chain([tile_idx])
+                .collect(),
+        };
+
+        let mut rng = self
+            .seed
+            .map_or_else(nanorand::WyRand::new, nanorand::WyRand::new_seed);
+        for _ in 0..OPENING_ATTEMPTS {
+            self.place_mines(&skip, &mut rng);
+            self.place_hints();
+
+            match self.params.first_click {
+                FirstClick::Opening(size) if self.opening_size(x, y) < size => continue,
+                _ => break,
+            }
+        }
     }
 
-    /// Place mines on the field.
+    /// Place mines on the field, replacing any previous layout.
     ///
     /// The `skip` argument contains board indices
     /// that shall not have a mine placed in.
-    fn place_mines(&mut self, skip: &[usize]) {
-        let mut rng = nanorand::WyRand::new();
+    ///
+    /// Every tile offers [Params::mines_per_tile] slots,
+    /// and mines are spread uniformly over all the slots.
+    fn place_mines(&mut self, skip: &[usize], rng: &mut nanorand::WyRand) {
+        for tile in &mut self.tiles {
+            tile.object = Object::Blank;
+        }
+
+        let per_tile = self.params.mines_per_tile as usize;
         let mines = (0..self.tiles.len())
             .filter(|i| !skip.contains(i))
-            .choose_multiple(&mut rng, self.params.mines);
+            .flat_map(|i| std::iter::repeat_n(i, per_tile))
+            .choose_multiple(rng, self.params.mines);
 
         for mine in mines {
-            self.tiles[mine].object = Object::Mine;
+            self.add_mine(mine);
         }
+
+        self.mined_tiles = self.tiles.iter().filter(|tile| tile.is_mine()).count();
+    }
+
+    fn add_mine(&mut self, idx: usize) {
+        let object = &mut self.tiles[idx].object;
+        *object = match *object {
+            Object::Mine(n) => Object::Mine(n + 1),
+            _ => Object::Mine(1),
+        };
+    }
+
+    /// Number of tiles a primary action on the given covered tile would uncover,
+    /// following the same rules as [Board::flood_uncover], but ignoring marks.
+    fn opening_size(&self, x: usize, y: usize) -> usize {
+        let mut size = 0;
+
+        flood(
+            (x, y),
+            |(x, y)| self.neighbors(x, y),
+            |(current_x, current_y), _| {
+                let tile = self.tile(current_x, current_y);
+
+                if tile.is_mine() {
+                    return Spill::Blocked;
+                }
+
+                size += 1;
+                if tile.is_blank() {
+                    Spill::Spread
+                } else {
+                    Spill::Stop
+                }
+            },
+        );
+
+        size
     }
 
     fn place_hints(&mut self) {
-        for x in 0..self.params.width {
-            for y in 0..self.params.height {
+        let (width, height) = self.dims();
+
+        for x in 0..width {
+            for y in 0..height {
                 let idx = self.coords_to_index(x, y);
                 if self.tiles[idx].is_mine() {
                     continue;
                 }
-                let mine_count = self
+                let mine_count: usize = self
                     .neighbors(x, y)
-                    .filter(|&(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].is_mine())
-                    .count();
+                    .map(|(xx, yy)| self.tiles[self.coords_to_index(xx, yy)].mines())
+                    .sum();
                 if mine_count > 0 {
-                    self.tiles[idx].object = Object::Hint(mine_count as _);
+                    let hint =
+                        u8::try_from(mine_count).expect("Hint out of range for neighborhood");
+                    self.tiles[idx].object = Object::Hint(hint);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Marks and flag counts seen while cycling the tile at `(0, 0)` `steps` times.
+    fn cycle_marks(params: Params, unsure_marks: bool, steps: usize) -> Vec<(Mark, usize)> {
+        let mut board = Board::new(params);
+        board.set_unsure_marks(unsure_marks);
+
+        (0..steps)
+            .map(|_| {
+                let outcome = board.handle_secondary_action(0, 0);
+                let Cover::Up(mark) = board.tile(0, 0).cover() else {
+                    panic!("marking uncovered a tile");
+                };
+                assert_eq!(outcome.marks[0].after, mark);
+                (mark, board.flags())
+            })
+            .collect()
+    }
+
+    /// Play the first click in the middle of a seeded board with each neighborhood.
+    fn first_clicks(first_click: FirstClick) -> impl Iterator<Item = (Board, ActionOutcome)> {
+        let neighborhoods = [
+            Neighborhood::VonNeumann,
+            Neighborhood::Moore,
+            Neighborhood::Radius2,
+            Neighborhood::Knight,
+        ];
+
+        neighborhoods.into_iter().flat_map(move |neighborhood| {
+            (0..50).map(move |seed| {
+                let mut board = Board::new(Params {
+                    first_click,
+                    neighborhood,
+                    ..INTERMEDIATE
+                });
+                board.set_seed(Some(seed));
+                let outcome = board.handle_primary_action(8, 8);
+                (board, outcome)
+            })
+        })
+    }
+
+    #[test]
+    fn safe_first_click_is_not_a_mine() {
+        for (board, outcome) in first_clicks(FirstClick::Safe) {
+            assert!(!board.tile(8, 8).is_mine());
+            assert_eq!(outcome.terminal, None);
+        }
+    }
+
+    #[test]
+    fn safe_area_first_click_has_no_mine_around() {
+        for (board, _) in first_clicks(FirstClick::SafeArea) {
+            assert!(board.tile(8, 8).is_blank());
+            assert!(board
+                .neighbors(8, 8)
+                .all(|(x, y)| !board.tile(x, y).is_mine()));
+        }
+    }
+
+    #[test]
+    fn opening_first_click_opens_enough_tiles() {
+        for (board, outcome) in first_clicks(FirstClick::Opening(20)) {
+            assert!(board.tile(8, 8).is_blank());
+            assert!(board
+                .neighbors(8, 8)
+                .all(|(x, y)| !board.tile(x, y).is_mine()));
+            assert!(outcome.revealed.len() >= 20, "{}", outcome.revealed.len());
+        }
+    }
+
+    #[test]
+    fn retry_replays_the_first_action_on_the_same_layout() {
+        let mut board = Board::beginner();
+        board.set_seed(Some(7));
+        let first = board.handle_primary_action(4, 4);
+        let layout: Vec<_> = board.tiles.iter().map(Tile::mines).collect();
+
+        board.handle_secondary_action(0, 0);
+        let mine = layout.iter().position(|&mines| mines > 0).unwrap();
+        board.handle_primary_action(mine % 8, mine / 8);
+        assert!(board.is_defeat());
+
+        let retried = board.retry();
+        assert_eq!(retried.revealed.len(), first.revealed.len());
+        assert_eq!(retried.terminal, None);
+        assert!(!board.is_defeat());
+        assert_eq!(board.flags(), 0);
+        assert_eq!(board.first_action(), Some((4, 4)));
+        assert_eq!(
+            board.tiles.iter().map(Tile::mines).collect::<Vec<_>>(),
+            layout
+        );
+    }
+
+    #[test]
+    fn retry_does_nothing_before_the_mines_are_placed() {
+        let mut board = Board::beginner();
+        assert!(board.retry().revealed.is_empty());
+        assert!(!board.is_initialized());
+    }
+
+    #[test]
+    fn three_bv_counts_openings_and_lone_hints() {
+        let mut walled = Board::new(Params {
+            width: 6,
+            height: 3,
+            mines: 3,
+            ..BEGINNER
+        });
+        walled.place_layout(&[(2, 0), (2, 1), (2, 2)]);
+        assert_eq!(walled.three_bv(), 2);
+
+        // `.1*1`, the hint right of the mine takes a click of its own
+        let mut row = Board::new(Params {
+            width: 4,
+            height: 1,
+            mines: 1,
+            ..BEGINNER
+        });
+        row.place_layout(&[(2, 0)]);
+        assert_eq!(row.three_bv(), 2);
+    }
+
+    #[test]
+    fn mark_cycles_through_unsure() {
+        assert_eq!(
+            cycle_marks(BEGINNER, true, 4),
+            [
+                (Mark::Flag(1), 1),
+                (Mark::Unsure, 0),
+                (Mark::None, 0),
+                (Mark::Flag(1), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn mark_cycles_without_unsure() {
+        assert_eq!(
+            cycle_marks(BEGINNER, false, 3),
+            [(Mark::Flag(1), 1), (Mark::None, 0), (Mark::Flag(1), 1)]
+        );
+    }
+
+    #[test]
+    fn mark_counts_up_flags_on_multi_mine_tiles() {
+        let params = Params {
+            mines_per_tile: 3,
+            ..BEGINNER
+        };
+
+        assert_eq!(
+            cycle_marks(params, true, 5),
+            [
+                (Mark::Flag(1), 1),
+                (Mark::Flag(2), 2),
+                (Mark::Flag(3), 3),
+                (Mark::Unsure, 0),
+                (Mark::None, 0),
+            ]
+        );
+        assert_eq!(
+            cycle_marks(params, false, 4),
+            [
+                (Mark::Flag(1), 1),
+                (Mark::Flag(2), 2),
+                (Mark::Flag(3), 3),
+                (Mark::None, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabling_unsure_marks_clears_existing_ones() {
+        let mut board = Board::beginner();
+        board.handle_secondary_action(0, 0);
+        board.handle_secondary_action(0, 0);
+        assert!(matches!(board.tile(0, 0).cover(), Cover::Up(Mark::Unsure)));
+
+        board.set_unsure_marks(false);
+        board.handle_secondary_action(0, 0);
+        assert!(matches!(board.tile(0, 0).cover(), Cover::Up(Mark::None)));
+        assert_eq!(board.flags(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one layer")]
+    fn layered_board_needs_a_layer() {
+        Board::new(Params {
+            topology: Topology::Layered(0),
+            ..BEGINNER
+        });
+    }
+}
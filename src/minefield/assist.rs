@@ -0,0 +1,246 @@
+use super::{ActionOutcome, Board, Cover, Mark, MarkChange, Object};
+
+/// Optional assistance levels, layered on top of the regular [Board] rules.
+///
+/// Every level is toggled individually.
+/// Games where any of them was active are tagged as assisted
+/// and do not count towards the pure leaderboard.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Assists {
+    /// Flag covered tiles around a hint that has exactly as many covered neighbors as hinted
+    /// (times [Params::mines_per_tile](super::Params::mines_per_tile)).
+    pub auto_flag: bool,
+
+    /// Explore around hints that already have all their flags placed.
+    pub auto_chord: bool,
+
+    /// Uncover everything that is left once there are as many flags as mines,
+    /// trusting the flags the way the player would.
+    pub auto_open: bool,
+}
+
+impl Assists {
+    pub fn any(&self) -> bool {
+        self.auto_flag || self.auto_chord || self.auto_open
+    }
+}
+
+impl Board {
+    /// Apply the enabled assists repeatedly, until none of them has anything left to do.
+    ///
+    /// Returns everything the assists did, as if it were a single action.
+    pub fn apply_assists(&mut self, assists: Assists) -> ActionOutcome {
+        let mut outcome = ActionOutcome::default();
+
+        while self.placed && !self.defeat && !self.is_victory() {
+            let step = (assists.auto_flag && self.auto_flag(&mut outcome))
+                || (assists.auto_chord && self.auto_chord(&mut outcome))
+                || (assists.auto_open && self.auto_open(&mut outcome));
+
+            if !step {
+                break;
+            }
+        }
+
+        self.check_invariants();
+        outcome.terminal = self.terminal(&outcome.revealed);
+        outcome
+    }
+
+    fn auto_flag(&mut self, outcome: &mut ActionOutcome) -> bool {
+        let mut changed = false;
+
+        for (x, y, hint) in self.uncovered_hints() {
+            let covered: Vec<_> = self
+                .neighbors(x, y)
+                .map(|pos| (pos, self.coords_to_index(pos.0, pos.1)))
+                .filter(|&(_, idx)| matches!(self.tiles[idx].cover, Cover::Up(_)))
+                .collect();
+
+            let exploded: usize = self
+                .neighbors(x, y)
+                .map(|(xx, yy)| self.tiles[self.coords_to_index(xx, yy)])
+                .filter(|tile| matches!(tile.cover, Cover::Down))
+                .map(|tile| tile.mines())
+                .sum();
+
+            // trivially mines only if every covered neighbor must be full to the brim
+            let per_tile = self.params.mines_per_tile;
+            if covered.len() * per_tile as usize + exploded != hint as usize {
+                continue;
+            }
+
+            for (pos, idx) in covered {
+                if let Cover::Up(mark) = &mut self.tiles[idx].cover {
+                    if *mark != Mark::Flag(per_tile) {
+                        outcome.marks.push(MarkChange {
+                            pos,
+                            before: *mark,
+                            after: Mark::Flag(per_tile),
+                        });
+
+                        self.flags = self.flags + per_tile as usize - mark.flags();
+                        *mark = Mark::Flag(per_tile);
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn auto_chord(&mut self, outcome: &mut ActionOutcome) -> bool {
+        let mut changed = false;
+
+        for (x, y, hint) in self.uncovered_hints() {
+            let revealed = self.explore_around(hint, x, y);
+            changed |= !revealed.is_empty();
+            outcome.revealed.extend(revealed);
+
+            if self.defeat {
+                break;
+            }
+        }
+
+        changed
+    }
+
+    fn auto_open(&mut self, outcome: &mut ActionOutcome) -> bool {
+        // only the count the player sees, so that opening tells nothing about the flags
+        if self.flags != self.params.mines {
+            return false;
+        }
+
+        let mut changed = false;
+
+        let (width, height) = self.dims();
+
+        for y in 0..height {
+            for x in 0..width {
+                let tile = self.tiles[self.coords_to_index(x, y)];
+                if !tile.is_uncoverable() || self.defeat {
+                    continue;
+                }
+
+                outcome.revealed.extend(self.uncover(x, y, 0));
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    fn uncovered_hints(&self) -> Vec<(usize, usize, u8)> {
+        let mut hints = vec![];
+
+        let (width, height) = self.dims();
+
+        for y in 0..height {
+            for x in 0..width {
+                let tile = self.tiles[self.coords_to_index(x, y)];
+                if let (Cover::Down, Object::Hint(hint)) = (tile.cover, tile.object) {
+                    hints.push((x, y, hint));
+                }
+            }
+        }
+
+        hints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::{Params, Terminal, BEGINNER};
+
+    const AUTO_FLAG: Assists = Assists {
+        auto_flag: true,
+        auto_chord: false,
+        auto_open: false,
+    };
+
+    const AUTO_CHORD: Assists = Assists {
+        auto_flag: false,
+        auto_chord: true,
+        auto_open: false,
+    };
+
+    const AUTO_OPEN: Assists = Assists {
+        auto_flag: false,
+        auto_chord: false,
+        auto_open: true,
+    };
+
+    fn two_mine_board() -> Board {
+        let mut board = Board::new(Params {
+            mines: 2,
+            ..BEGINNER
+        });
+        board.place_layout(&[(0, 0), (7, 7)]);
+        board
+    }
+
+    #[test]
+    fn auto_flag_fills_hints_with_as_many_covered_neighbors() {
+        // a wall of mines down the third column, uncovered up to it
+        let mut board = Board::new(Params {
+            width: 6,
+            height: 3,
+            mines: 3,
+            ..BEGINNER
+        });
+        board.place_layout(&[(2, 0), (2, 1), (2, 2)]);
+        board.handle_primary_action(0, 0);
+
+        let outcome = board.apply_assists(AUTO_FLAG);
+        assert_eq!(outcome.marks.len(), 3);
+        assert_eq!(outcome.terminal, None);
+        assert_eq!(board.flags(), 3);
+        for y in 0..3 {
+            assert_eq!(board.tile(2, y).flags(), 1);
+        }
+    }
+
+    #[test]
+    fn auto_chord_explores_around_fully_flagged_hints() {
+        let mut board = Board::new(Params {
+            width: 3,
+            height: 3,
+            mines: 2,
+            ..BEGINNER
+        });
+        board.place_layout(&[(0, 0), (2, 2)]);
+        board.handle_primary_action(1, 0);
+        board.handle_secondary_action(0, 0);
+
+        // the unflagged mine is left alone, as no hint next to it is fully flagged
+        let outcome = board.apply_assists(AUTO_CHORD);
+        assert!(!outcome.revealed.is_empty());
+        assert_eq!(outcome.terminal, Some(Terminal::Victory));
+        assert!(matches!(board.tile(2, 2).cover(), Cover::Up(Mark::None)));
+    }
+
+    #[test]
+    fn auto_open_trusts_misplaced_flags() {
+        let mut board = two_mine_board();
+        board.handle_secondary_action(0, 0);
+        board.handle_secondary_action(3, 3);
+
+        let outcome = board.apply_assists(AUTO_OPEN);
+        assert_eq!(
+            outcome.terminal,
+            Some(Terminal::Defeat { triggered: (7, 7) })
+        );
+    }
+
+    #[test]
+    fn auto_open_clears_the_board_once_every_mine_is_flagged() {
+        let mut board = two_mine_board();
+        board.handle_secondary_action(0, 0);
+        board.handle_secondary_action(7, 7);
+
+        let outcome = board.apply_assists(AUTO_OPEN);
+        assert_eq!(outcome.terminal, Some(Terminal::Victory));
+    }
+}
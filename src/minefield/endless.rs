@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use super::flood::{flood, Spill};
+use super::{Cover, Neighborhood, Object, Tile};
+use crate::random::{mix, IteratorRandom};
+
+/// Side length of a square chunk of tiles.
+pub const CHUNK_SIZE: i64 = 16;
+
+/// Mines in every chunk.
+///
+/// Kept dense enough (about 15%) that blanks, about a quarter of the tiles,
+/// are too rare to join into an endless region in practice.
+/// Nothing else bounds a flood fill, which stops only once it runs out of blanks.
+const CHUNK_MINES: usize = 40;
+
+/// A board without edges, generated chunk by chunk as it gets explored.
+///
+/// The mine layout of every chunk is a pure function of the board seed
+/// and the chunk coordinates, so chunks can be generated lazily, in any order,
+/// and the same seed always yields the same board.
+///
+/// The tiles around `(0, 0)` are always mine-free, and the board starts
+/// with `(0, 0)` already uncovered.
+///
+/// The rules are the classic ones: [Neighborhood::Moore] neighbors
+/// and a single mine per tile.
+#[derive(Debug)]
+pub struct EndlessBoard {
+    seed: u64,
+    chunks: HashMap<(i64, i64), Vec<Tile>>,
+    /// See [Board::set_unsure_marks](super::Board::set_unsure_marks).
+    unsure_marks: bool,
+    cleared: usize,
+    exploded: Option<(i64, i64)>,
+}
+
+impl EndlessBoard {
+    /// A fresh board, with marks following the given unsure-marks rule option.
+    pub fn new(seed: u64, unsure_marks: bool) -> Self {
+        let mut board = Self {
+            seed,
+            chunks: HashMap::new(),
+            unsure_marks,
+            cleared: 0,
+            exploded: None,
+        };
+
+        board.handle_primary_action(0, 0);
+        board
+    }
+
+    /// The tile at the given position, or `None` if its chunk was not generated yet.
+    ///
+    /// Tiles in chunks that were never generated are untouched, i.e. covered and unmarked.
+    pub fn tile(&self, x: i64, y: i64) -> Option<Tile> {
+        let (chunk, idx) = chunk_of(x, y);
+        self.chunks.get(&chunk).map(|tiles| tiles[idx])
+    }
+
+    /// Number of safe tiles uncovered so far, which is the score of an endless game.
+    pub fn cleared(&self) -> usize {
+        self.cleared
+    }
+
+    /// Position of the mine that ended the game, if any.
+    pub fn exploded(&self) -> Option<(i64, i64)> {
+        self.exploded
+    }
+
+    /// Make sure all chunks within `radius` chunks of the given tile are generated.
+    pub fn generate_around(&mut self, x: i64, y: i64, radius: i64) {
+        let ((chunk_x, chunk_y), _) = chunk_of(x, y);
+
+        for off_y in -radius..=radius {
+            for off_x in -radius..=radius {
+                self.chunk_mut((chunk_x + off_x, chunk_y + off_y));
+            }
+        }
+    }
+
+    /// Same as [Board::handle_primary_action](super::Board::handle_primary_action),
+    /// on an endless board.
+    pub fn handle_primary_action(&mut self, x: i64, y: i64) {
+        if self.exploded.is_some() {
+            return;
+        }
+
+        let tile = *self.tile_mut(x, y);
+
+        if let (Cover::Down, Object::Hint(hint)) = (tile.cover, tile.object) {
+            self.explore_around(hint, x, y);
+        } else if tile.is_uncoverable() {
+            self.uncover(x, y);
+        }
+    }
+
+    /// Same as [Board::handle_secondary_action](super::Board::handle_secondary_action),
+    /// on an endless board.
+    pub fn handle_secondary_action(&mut self, x: i64, y: i64) {
+        if self.exploded.is_some() {
+            return;
+        }
+
+        let unsure_marks = self.unsure_marks;
+        if let Cover::Up(mark) = &mut self.tile_mut(x, y).cover {
+            mark.cycle(unsure_marks, 1);
+        }
+    }
+
+    fn neighbors(x: i64, y: i64) -> impl Iterator<Item = (i64, i64)> {
+        Neighborhood::Moore
+            .offsets()
+            .into_iter()
+            .map(move |(off_x, off_y)| (x + off_x as i64, y + off_y as i64))
+    }
+
+    fn uncover(&mut self, x: i64, y: i64) {
+        flood(
+            (x, y),
+            |(x, y)| Self::neighbors(x, y),
+            |(current_x, current_y), _| {
+                let tile = self.tile_mut(current_x, current_y);
+
+                if !tile.is_uncoverable() {
+                    return Spill::Blocked;
+                }
+
+                tile.cover = Cover::Down;
+                let object = tile.object;
+
+                match object {
+                    Object::Mine(_) => {
+                        self.exploded = Some((current_x, current_y));
+                        return Spill::Stop;
+                    }
+                    Object::Blank | Object::Hint(_) => self.cleared += 1,
+                }
+
+                if let Object::Blank = object {
+                    Spill::Spread
+                } else {
+                    Spill::Stop
+                }
+            },
+        );
+    }
+
+    fn explore_around(&mut self, hinted: u8, x: i64, y: i64) {
+        let neighbors: Vec<_> = Self::neighbors(x, y).collect();
+        let n_flags: usize = neighbors
+            .iter()
+            .map(|&(xx, yy)| self.tile_mut(xx, yy).flags())
+            .sum();
+
+        if hinted as usize != n_flags {
+            return;
+        }
+
+        for (current_x, current_y) in neighbors {
+            if self.exploded.is_none() && self.tile_mut(current_x, current_y).is_uncoverable() {
+                self.uncover(current_x, current_y);
+            }
+        }
+    }
+
+    fn tile_mut(&mut self, x: i64, y: i64) -> &mut Tile {
+        let (chunk, idx) = chunk_of(x, y);
+        &mut self.chunk_mut(chunk)[idx]
+    }
+
+    fn chunk_mut(&mut self, chunk: (i64, i64)) -> &mut Vec<Tile> {
+        let seed = self.seed;
+        self.chunks
+            .entry(chunk)
+            .or_insert_with(|| generate_chunk(seed, chunk))
+    }
+}
+
+/// Chunk coordinates of a tile, and its index within the chunk.
+fn chunk_of(x: i64, y: i64) -> ((i64, i64), usize) {
+    let chunk = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+    let (local_x, local_y) = (x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE));
+
+    (chunk, (local_y * CHUNK_SIZE + local_x) as usize)
+}
+
+fn generate_chunk(seed: u64, (chunk_x, chunk_y): (i64, i64)) -> Vec<Tile> {
+    // hints on the chunk edges depend on the mines of the surrounding chunks
+    let mut layouts = HashMap::new();
+    for off_y in -1..=1 {
+        for off_x in -1..=1 {
+            let chunk = (chunk_x + off_x, chunk_y + off_y);
+            layouts.insert(chunk, mine_layout(seed, chunk));
+        }
+    }
+
+    let is_mine = |x: i64, y: i64| {
+        let (chunk, idx) = chunk_of(x, y);
+        layouts[&chunk][idx]
+    };
+
+    let mut tiles = vec![Tile::new(); (CHUNK_SIZE * CHUNK_SIZE) as usize];
+    for local_y in 0..CHUNK_SIZE {
+        for local_x in 0..CHUNK_SIZE {
+            let x = chunk_x * CHUNK_SIZE + local_x;
+            let y = chunk_y * CHUNK_SIZE + local_y;
+            let tile = &mut tiles[(local_y * CHUNK_SIZE + local_x) as usize];
+
+            if is_mine(x, y) {
+                tile.object = Object::Mine(1);
+                continue;
+            }
+
+            let mine_count = EndlessBoard::neighbors(x, y)
+                .filter(|&(xx, yy)| is_mine(xx, yy))
+                .count();
+            if mine_count > 0 {
+                tile.object = Object::Hint(mine_count as _);
+            }
+        }
+    }
+
+    tiles
+}
+
+/// Which tiles of a chunk hold a mine, derived from the board seed alone.
+fn mine_layout(seed: u64, (chunk_x, chunk_y): (i64, i64)) -> Vec<bool> {
+    let chunk_seed = mix(seed ^ mix(chunk_x as u64) ^ mix(chunk_y as u64).rotate_left(32));
+    let mut rng = nanorand::WyRand::new_seed(chunk_seed);
+
+    let is_start_area = |idx: i64| {
+        let x = chunk_x * CHUNK_SIZE + idx % CHUNK_SIZE;
+        let y = chunk_y * CHUNK_SIZE + idx / CHUNK_SIZE;
+        x.abs() <= 1 && y.abs() <= 1
+    };
+
+    let mut layout = vec![false; (CHUNK_SIZE * CHUNK_SIZE) as usize];
+    let mines = (0..CHUNK_SIZE * CHUNK_SIZE)
+        .filter(|&idx| !is_start_area(idx))
+        .choose_multiple(&mut rng, CHUNK_MINES);
+
+    for mine in mines {
+        layout[mine as usize] = true;
+    }
+
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::Mark;
+
+    #[test]
+    fn start_opens_a_blank_area_counted_as_cleared() {
+        for seed in 0..20 {
+            let board = EndlessBoard::new(seed, true);
+            let start = board.tile(0, 0).unwrap();
+            assert!(matches!(start.cover(), Cover::Down));
+            assert!(start.is_blank());
+
+            let uncovered = board
+                .chunks
+                .values()
+                .flatten()
+                .filter(|tile| matches!(tile.cover(), Cover::Down))
+                .count();
+            assert_eq!(board.cleared(), uncovered);
+            assert!(board.cleared() >= 9);
+            assert_eq!(board.exploded(), None);
+        }
+    }
+
+    #[test]
+    fn marks_follow_the_unsure_marks_rule() {
+        for unsure_marks in [true, false] {
+            let mut board = EndlessBoard::new(0, unsure_marks);
+            let covered = (1..)
+                .map(|x| (x, 0))
+                .find(|&(x, y)| board.tile(x, y).unwrap().is_uncoverable())
+                .unwrap();
+
+            board.handle_secondary_action(covered.0, covered.1);
+            board.handle_secondary_action(covered.0, covered.1);
+
+            let expected = if unsure_marks {
+                Mark::Unsure
+            } else {
+                Mark::None
+            };
+            assert!(matches!(
+                board.tile(covered.0, covered.1).unwrap().cover(),
+                Cover::Up(mark) if mark == expected
+            ));
+        }
+    }
+}
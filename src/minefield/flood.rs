@@ -0,0 +1,37 @@
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+/// What a flood fill does once it reaches a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spill {
+    /// The tile is not part of the flood, like a flagged or mined one.
+    Blocked,
+    /// The tile is part of the flood, which stops there, like on a hint.
+    Stop,
+    /// The tile is part of the flood, which goes on to its neighbors, like on a blank.
+    Spread,
+}
+
+/// Breadth-first flood fill from `start`, the way uncovering a blank tile spills over.
+///
+/// `visit` gets called once for every tile the flood reaches, along with its distance
+/// from `start`, and decides where the flood goes from there.
+/// The tiles are visited nearest first.
+pub fn flood<P, N>(start: P, neighbors: impl Fn(P) -> N, mut visit: impl FnMut(P, usize) -> Spill)
+where
+    P: Copy + Eq + Hash,
+    N: IntoIterator<Item = P>,
+{
+    let mut flooded = VecDeque::from([(start, 0)]);
+    let mut visited = HashSet::new();
+
+    while let Some((current, depth)) = flooded.pop_front() {
+        if !visited.insert(current) {
+            continue;
+        }
+
+        if visit(current, depth) == Spill::Spread {
+            flooded.extend(neighbors(current).into_iter().map(|pos| (pos, depth + 1)));
+        }
+    }
+}
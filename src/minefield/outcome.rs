@@ -0,0 +1,63 @@
+use super::{Mark, Object};
+
+/// Everything a single action did to a [Board](super::Board),
+/// for the game to react to without comparing board states.
+#[derive(Debug, Default, Clone)]
+pub struct ActionOutcome {
+    /// Tiles uncovered by the action, in the order they were uncovered,
+    /// which spreads from the action's tile outwards when flooding.
+    pub revealed: Vec<Reveal>,
+
+    /// Marks placed or removed by the action.
+    pub marks: Vec<MarkChange>,
+
+    /// The action was the first of the game, and placed the mines.
+    pub initialized: bool,
+
+    /// The action ended the game, `None` if it goes on.
+    pub terminal: Option<Terminal>,
+}
+
+/// A tile uncovered by an action.
+#[derive(Debug, Clone, Copy)]
+pub struct Reveal {
+    pub pos: (usize, usize),
+    pub object: Object,
+
+    /// Flood-fill steps from where the uncovering started, 0 for the tile acted upon.
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkChange {
+    pub pos: (usize, usize),
+    pub before: Mark,
+    pub after: Mark,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminal {
+    /// Every tile without a mine is uncovered.
+    Victory,
+
+    /// A mine was uncovered, the first one of the action at `triggered`.
+    Defeat { triggered: (usize, usize) },
+}
+
+impl ActionOutcome {
+    /// Number of mined tiles among the revealed ones.
+    pub fn explosions(&self) -> usize {
+        self.revealed
+            .iter()
+            .filter(|reveal| matches!(reveal.object, Object::Mine(_)))
+            .count()
+    }
+
+    /// Append the outcome of an action that directly followed this one.
+    pub fn extend(&mut self, next: ActionOutcome) {
+        self.revealed.extend(next.revealed);
+        self.marks.extend(next.marks);
+        self.initialized |= next.initialized;
+        self.terminal = next.terminal.or(self.terminal);
+    }
+}
@@ -0,0 +1,688 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::{Board, Cover, Mark, Object, Tile};
+
+/// Largest number of covered tiles considered at once when looking for a deduction.
+///
+/// Keeps the search around a single tile fast. Tiles in denser tangles
+/// are left undecided, which can only make the solver more conservative.
+const MAX_LOCAL_TILES: usize = 30;
+
+/// Largest number of mine arrangements counted for a probability,
+/// as bounded by the number of tiles and how many mines each of them can hold.
+///
+/// Counting cannot stop at the first arrangement found, hence a tighter bound
+/// than [MAX_LOCAL_TILES]. Denser tangles fall back to the average mine density.
+const MAX_ARRANGEMENTS: u64 = 1 << 20;
+
+/// A covered tile whose number of mines follows from what the player can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deduction {
+    pub pos: (usize, usize),
+
+    /// 0 for a safe tile.
+    pub mines: u8,
+}
+
+/// A tile pointed out to a player who asked for help, see [Board::hint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hint {
+    /// The tile is provably free of mines.
+    Safe((usize, usize)),
+
+    /// The tile provably holds more mines than flagged on it.
+    Mine((usize, usize)),
+
+    /// Nothing is provable, the tile is the least likely to hold a mine.
+    Guess((usize, usize)),
+}
+
+/// Mines left to find and the covered tiles they can be on,
+/// once the uncovered and deduced mines are accounted for.
+#[derive(Debug, Clone, Copy)]
+struct Unsettled {
+    mines: usize,
+    tiles: usize,
+    per_tile: u8,
+}
+
+/// An uncovered tile's hint, as a requirement on the covered tiles around it.
+#[derive(Debug)]
+struct Constraint {
+    tiles: Vec<(usize, usize)>,
+    mines: usize,
+}
+
+impl Unsettled {
+    /// Average number of mines on a tile, as a share of the most it can hold.
+    fn density(self) -> f32 {
+        self.mines as f32 / (self.tiles.max(1) as f32 * self.per_tile as f32)
+    }
+}
+
+impl Board {
+    /// Every covered tile whose content follows for sure from the uncovered hints
+    /// and the total number of mines, ignoring any marks the player placed.
+    pub fn deductions(&self) -> Vec<Deduction> {
+        let mut known = HashMap::new();
+
+        loop {
+            let constraints = self.constraints(&known);
+            let frontier: BTreeSet<_> = constraints
+                .iter()
+                .flat_map(|constraint| constraint.tiles.iter().copied())
+                .collect();
+
+            let mut progress = false;
+            for pos in frontier {
+                if let Some(mines) = self.forced_mines(&constraints, pos) {
+                    known.insert(pos, mines);
+                    progress = true;
+                }
+            }
+
+            if !progress {
+                break;
+            }
+        }
+
+        // whatever the hints left open may still be settled by the mine total
+        let (width, height) = self.dims();
+        let unknown: Vec<_> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_covered(x, y) && !known.contains_key(&(x, y)))
+            .collect();
+        let exploded: usize = self
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile.cover, Cover::Down))
+            .map(Tile::mines)
+            .sum();
+        let known_mines = exploded + known.values().map(|&mines| mines as usize).sum::<usize>();
+        let remaining = self.params.mines.saturating_sub(known_mines);
+        let per_tile = self.params.mines_per_tile;
+
+        if remaining == 0 {
+            known.extend(unknown.into_iter().map(|pos| (pos, 0)));
+        } else if remaining == unknown.len() * per_tile as usize {
+            known.extend(unknown.into_iter().map(|pos| (pos, per_tile)));
+        }
+
+        let mut deductions: Vec<_> = known
+            .into_iter()
+            .map(|(pos, mines)| Deduction { pos, mines })
+            .collect();
+        deductions.sort_by_key(|deduction| (deduction.pos.1, deduction.pos.0));
+
+        deductions
+    }
+
+    /// Whether the board can be cleared from its current state
+    /// by deductions alone, without ever having to guess.
+    pub fn is_solvable_without_guessing(&self) -> bool {
+        let mut board = self.clone();
+
+        // marks are the player's opinion, not something to reason from
+        for tile in &mut board.tiles {
+            if let Cover::Up(mark) = &mut tile.cover {
+                *mark = Mark::None;
+            }
+        }
+        board.flags = 0;
+
+        loop {
+            if board.is_victory() {
+                return true;
+            }
+            if board.is_defeat() {
+                return false;
+            }
+
+            let safe: Vec<_> = board
+                .deductions()
+                .into_iter()
+                .filter(|deduction| deduction.mines == 0)
+                .collect();
+
+            if safe.is_empty() {
+                return false;
+            }
+
+            for Deduction { pos: (x, y), .. } in safe {
+                board.uncover(x, y, 0);
+            }
+        }
+    }
+
+    /// The most useful tile to point out: one proven safe, else a mine left unflagged,
+    /// else the safest guess. `None` when no covered tile is left to act on.
+    pub fn hint(&self) -> Option<Hint> {
+        let deductions = self.deductions();
+
+        let safe = deductions
+            .iter()
+            .find(|d| d.mines == 0 && self.tile(d.pos.0, d.pos.1).is_uncoverable());
+        if let Some(safe) = safe {
+            return Some(Hint::Safe(safe.pos));
+        }
+
+        let unflagged_mine = deductions
+            .iter()
+            .find(|d| self.tile(d.pos.0, d.pos.1).flags() < d.mines as usize);
+        if let Some(mine) = unflagged_mine {
+            return Some(Hint::Mine(mine.pos));
+        }
+
+        let known: HashMap<_, _> = deductions.iter().map(|d| (d.pos, d.mines)).collect();
+        let unsettled = self.unsettled(&known);
+        let constraints = self.constraints(&known);
+
+        let mut odds = vec![];
+        for region in regions(&constraints) {
+            match self.region_probabilities(&constraints, &region, unsettled) {
+                Some(probabilities) => odds.extend(region.into_iter().zip(probabilities)),
+                // too tangled to count as a whole, each tile is left to the hints around it
+                None => odds.extend(
+                    region
+                        .into_iter()
+                        .map(|(x, y)| ((x, y), self.mine_probability_given(&deductions, x, y))),
+                ),
+            }
+        }
+
+        // tiles away from the hints all share the same odds, one of them is enough
+        let (width, height) = self.dims();
+        let inland = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|pos| self.is_covered(pos.0, pos.1) && !known.contains_key(pos))
+            .find(|pos| !odds.iter().any(|(tile, _)| tile == pos));
+        odds.extend(inland.map(|pos| (pos, unsettled.density())));
+
+        odds.into_iter()
+            .filter(|&((x, y), _)| self.tile(x, y).is_uncoverable())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(pos, _)| Hint::Guess(pos))
+    }
+
+    /// Chance that a tile holds a mine, given what the player can see.
+    ///
+    /// Arrangements of mines around the tile that fit the hints nearby are weighted
+    /// by how many ways the remaining mines fit on the rest of the covered tiles,
+    /// which ignores hints further away, and mine counts on multi-mine boards.
+    /// Tiles away from any hint get the average density of the mines left.
+    pub fn mine_probability(&self, x: usize, y: usize) -> f32 {
+        self.mine_probability_given(&self.deductions(), x, y)
+    }
+
+    /// [Board::mine_probability], with the board's deductions already at hand.
+    fn mine_probability_given(&self, deductions: &[Deduction], x: usize, y: usize) -> f32 {
+        if !self.is_covered(x, y) {
+            return if self.tile(x, y).is_mine() { 1. } else { 0. };
+        }
+
+        if let Some(deduction) = deductions.iter().find(|d| d.pos == (x, y)) {
+            return if deduction.mines > 0 { 1. } else { 0. };
+        }
+
+        let known: HashMap<_, _> = deductions.iter().map(|d| (d.pos, d.mines)).collect();
+        let unsettled = self.unsettled(&known);
+        let constraints = self.constraints(&known);
+
+        local_tiles(&constraints, (x, y))
+            .and_then(|tiles| self.region_probabilities(&constraints, &tiles, unsettled))
+            .map_or(unsettled.density(), |probabilities| probabilities[0])
+    }
+
+    /// What is left to find once the uncovered mines and those in `known` are accounted for.
+    fn unsettled(&self, known: &HashMap<(usize, usize), u8>) -> Unsettled {
+        let uncovered_mines: usize = self
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile.cover, Cover::Down))
+            .map(Tile::mines)
+            .sum();
+        let known_mines: usize = known.values().map(|&mines| mines as usize).sum();
+
+        let covered_tiles = self
+            .tiles
+            .iter()
+            .filter(|tile| matches!(tile.cover, Cover::Up(_)))
+            .count();
+
+        Unsettled {
+            mines: self
+                .params
+                .mines
+                .saturating_sub(uncovered_mines + known_mines),
+            tiles: covered_tiles - known.len(),
+            per_tile: self.params.mines_per_tile,
+        }
+    }
+
+    /// Chance of a mine on each of `tiles`, in order, counted over every arrangement
+    /// of mines on them fitting the constraints touching them, and weighted
+    /// by how many ways the remaining mines fit on the unsettled tiles outside of them.
+    ///
+    /// `None` when there are too many arrangements to count, or none fits.
+    fn region_probabilities(
+        &self,
+        constraints: &[Constraint],
+        tiles: &[(usize, usize)],
+        unsettled: Unsettled,
+    ) -> Option<Vec<f32>> {
+        let per_tile = unsettled.per_tile;
+        let arrangements = (per_tile as u64 + 1).checked_pow(tiles.len() as u32);
+        if arrangements.is_none_or(|arrangements| arrangements > MAX_ARRANGEMENTS) {
+            return None;
+        }
+
+        let local: Vec<_> = constraints
+            .iter()
+            .filter(|constraint| constraint.tiles.iter().any(|tile| tiles.contains(tile)))
+            .collect();
+
+        // ways of fitting the mines not in the region on the covered tiles outside of it,
+        // relative to one another, as the logarithm of a binomial coefficient
+        let outside = unsettled.tiles - tiles.len();
+        let ln_ways = |region_mines: usize| -> Option<f64> {
+            let rest = unsettled.mines.checked_sub(region_mines)?;
+            (rest <= outside).then(|| {
+                (1..=rest)
+                    .map(|i| ((outside - rest + i) as f64 / i as f64).ln())
+                    .sum()
+            })
+        };
+
+        // arrangements fitting the hints, and those mining each tile, by mines in the region
+        let mut counts = vec![(0u64, vec![0u64; tiles.len()]); tiles.len() * per_tile as usize + 1];
+        let mut search = Search::new(tiles, &local, per_tile);
+        search.enumerate(0, &mut vec![], &mut |mines: &[u8]| {
+            let region_mines: usize = mines.iter().map(|&m| m as usize).sum();
+            let (fitting, mined) = &mut counts[region_mines];
+            *fitting += 1;
+            for (mined, &mines) in mined.iter_mut().zip(mines) {
+                *mined += u64::from(mines > 0);
+            }
+        });
+
+        let weighted: Vec<_> = counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (fitting, _))| *fitting > 0)
+            .filter_map(|(region_mines, counts)| Some((ln_ways(region_mines)?, counts)))
+            .collect();
+
+        // scaled by the most likely mine count, to keep the exponentials in range
+        let max = weighted.iter().map(|&(w, _)| w).reduce(f64::max)?;
+        let mut total = 0.;
+        let mut mined = vec![0.; tiles.len()];
+        for (w, (fitting, fitting_mined)) in weighted {
+            let scale = (w - max).exp();
+            total += fitting as f64 * scale;
+            for (mined, fitting_mined) in mined.iter_mut().zip(fitting_mined) {
+                *mined += fitting_mined as f64 * scale;
+            }
+        }
+
+        Some(
+            mined
+                .into_iter()
+                .map(|mined| (mined / total) as f32)
+                .collect(),
+        )
+    }
+
+    fn is_covered(&self, x: usize, y: usize) -> bool {
+        matches!(self.tile(x, y).cover, Cover::Up(_))
+    }
+
+    /// Requirements set by every uncovered hint with covered neighbors,
+    /// on the tiles not already in `known`.
+    fn constraints(&self, known: &HashMap<(usize, usize), u8>) -> Vec<Constraint> {
+        let (width, height) = self.dims();
+        let mut constraints = vec![];
+
+        for y in 0..height {
+            for x in 0..width {
+                let tile = self.tile(x, y);
+                let hint = match (tile.cover, tile.object) {
+                    (Cover::Down, Object::Hint(hint)) => hint as usize,
+                    (Cover::Down, Object::Blank) => 0,
+                    _ => continue,
+                };
+
+                let mut mines = hint;
+                let mut tiles = vec![];
+                for (xx, yy) in self.neighbors(x, y) {
+                    // mines exploded in a game that went on are as good as known
+                    if !self.is_covered(xx, yy) {
+                        mines = mines.saturating_sub(self.tile(xx, yy).mines());
+                        continue;
+                    }
+
+                    match known.get(&(xx, yy)) {
+                        Some(&known_mines) => mines = mines.saturating_sub(known_mines as usize),
+                        None => tiles.push((xx, yy)),
+                    }
+                }
+
+                if !tiles.is_empty() {
+                    constraints.push(Constraint { tiles, mines });
+                }
+            }
+        }
+
+        constraints
+    }
+
+    /// The only number of mines `pos` can hold given the constraints around it, if there is one.
+    ///
+    /// Only looks at the constraints sharing a tile with those touching `pos`,
+    /// which is enough for a proof, but may miss deductions needing a wider view.
+    fn forced_mines(&self, constraints: &[Constraint], pos: (usize, usize)) -> Option<u8> {
+        let tiles = local_tiles(constraints, pos)?;
+
+        if tiles.len() > MAX_LOCAL_TILES {
+            return None;
+        }
+
+        let local: Vec<_> = constraints
+            .iter()
+            .filter(|constraint| constraint.tiles.iter().any(|tile| tiles.contains(tile)))
+            .collect();
+
+        let mut search = Search::new(&tiles, &local, self.params.mines_per_tile);
+        let mut possible =
+            (0..=self.params.mines_per_tile).filter(|&mines| search.try_first(mines));
+
+        let mines = possible.next()?;
+        possible.next().is_none().then_some(mines)
+    }
+}
+
+/// Every tile in a constraint, in groups linked by the constraints they share,
+/// so that the mines of one group say nothing about those of another.
+fn regions(constraints: &[Constraint]) -> Vec<Vec<(usize, usize)>> {
+    let mut seen = BTreeSet::new();
+    let mut regions = vec![];
+
+    for &start in constraints.iter().flat_map(|constraint| &constraint.tiles) {
+        if !seen.insert(start) {
+            continue;
+        }
+
+        let mut region = vec![];
+        let mut stack = vec![start];
+        while let Some(tile) = stack.pop() {
+            region.push(tile);
+
+            let linked = constraints
+                .iter()
+                .filter(|constraint| constraint.tiles.contains(&tile))
+                .flat_map(|constraint| &constraint.tiles);
+            for &next in linked {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        regions.push(region);
+    }
+
+    regions
+}
+
+/// Tiles sharing a constraint with those around `pos`, starting with `pos` itself,
+/// `None` if no hint touches `pos`.
+fn local_tiles(constraints: &[Constraint], pos: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+    let region: BTreeSet<_> = constraints
+        .iter()
+        .filter(|constraint| constraint.tiles.contains(&pos))
+        .flat_map(|constraint| constraint.tiles.iter().copied())
+        .collect();
+
+    if region.is_empty() {
+        return None;
+    }
+
+    // the tile in question goes first, so it is the one tried with every value
+    let mut tiles = vec![pos];
+    let local = constraints
+        .iter()
+        .filter(|constraint| constraint.tiles.iter().any(|tile| region.contains(tile)));
+
+    for constraint in local {
+        for &tile in &constraint.tiles {
+            if !tiles.contains(&tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+
+    Some(tiles)
+}
+
+/// Backtracking over the mine counts of a handful of tiles,
+/// looking for any assignment satisfying all the given constraints.
+struct Search {
+    per_tile: u8,
+    /// Indices of the constraints each tile is part of.
+    constraints_of: Vec<Vec<usize>>,
+    /// Mines each constraint still needs.
+    missing: Vec<isize>,
+    /// Tiles of each constraint without a mine count yet.
+    open: Vec<usize>,
+}
+
+impl Search {
+    fn new(tiles: &[(usize, usize)], constraints: &[&Constraint], per_tile: u8) -> Self {
+        let constraints_of = tiles
+            .iter()
+            .map(|tile| {
+                (0..constraints.len())
+                    .filter(|&idx| constraints[idx].tiles.contains(tile))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            per_tile,
+            constraints_of,
+            missing: constraints.iter().map(|c| c.mines as isize).collect(),
+            open: constraints.iter().map(|c| c.tiles.len()).collect(),
+        }
+    }
+
+    /// Whether there is a full assignment giving the first tile exactly `mines` mines.
+    fn try_first(&mut self, mines: u8) -> bool {
+        self.assign(0, mines) && {
+            let found = self.search(1);
+            self.unassign(0, mines);
+            found
+        }
+    }
+
+    fn search(&mut self, tile: usize) -> bool {
+        if tile == self.constraints_of.len() {
+            return true;
+        }
+
+        for mines in 0..=self.per_tile {
+            if !self.assign(tile, mines) {
+                continue;
+            }
+
+            let found = self.search(tile + 1);
+            self.unassign(tile, mines);
+
+            if found {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Call `visit` with every full assignment, given the mines of the tiles before `tile`.
+    fn enumerate(&mut self, tile: usize, mines: &mut Vec<u8>, visit: &mut impl FnMut(&[u8])) {
+        if tile == self.constraints_of.len() {
+            visit(mines);
+            return;
+        }
+
+        for count in 0..=self.per_tile {
+            if !self.assign(tile, count) {
+                continue;
+            }
+
+            mines.push(count);
+            self.enumerate(tile + 1, mines, visit);
+            mines.pop();
+            self.unassign(tile, count);
+        }
+    }
+
+    /// Give a tile its mines, unless that makes a constraint impossible to satisfy.
+    fn assign(&mut self, tile: usize, mines: u8) -> bool {
+        for &idx in &self.constraints_of[tile] {
+            self.missing[idx] -= mines as isize;
+            self.open[idx] -= 1;
+        }
+
+        let consistent = self.constraints_of[tile].iter().all(|&idx| {
+            let missing = self.missing[idx];
+            missing >= 0 && missing <= (self.open[idx] * self.per_tile as usize) as isize
+        });
+
+        if !consistent {
+            self.unassign(tile, mines);
+        }
+
+        consistent
+    }
+
+    fn unassign(&mut self, tile: usize, mines: u8) {
+        for &idx in &self.constraints_of[tile] {
+            self.missing[idx] += mines as isize;
+            self.open[idx] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::{Params, BEGINNER};
+
+    /// A board of the given size with mines on the listed tiles, after one click.
+    fn played(
+        width: usize,
+        height: usize,
+        mines: &[(usize, usize)],
+        click: (usize, usize),
+    ) -> Board {
+        let mut board = Board::new(Params {
+            width,
+            height,
+            mines: mines.len(),
+            ..BEGINNER
+        });
+        board.place_layout(mines);
+        board.handle_primary_action(click.0, click.1);
+        board
+    }
+
+    /// `.o*.`, the hint leaves only one place for the mine, and the total clears the rest.
+    fn no_guess() -> Board {
+        played(4, 1, &[(2, 0)], (0, 0))
+    }
+
+    /// ```text
+    /// *1..
+    /// 11..
+    /// ```
+    /// with the mine equally likely on either tile of the left column.
+    fn fifty_fifty() -> Board {
+        played(4, 2, &[(0, 0)], (3, 1))
+    }
+
+    #[test]
+    fn deduces_mines_from_hints_and_safe_tiles_from_the_total() {
+        assert_eq!(
+            no_guess().deductions(),
+            [
+                Deduction {
+                    pos: (2, 0),
+                    mines: 1
+                },
+                Deduction {
+                    pos: (3, 0),
+                    mines: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deductions_ignore_misplaced_flags() {
+        let mut board = no_guess();
+        board.handle_secondary_action(3, 0);
+        assert_eq!(board.deductions(), no_guess().deductions());
+    }
+
+    #[test]
+    fn deduces_nothing_on_a_fifty_fifty() {
+        assert!(fifty_fifty().deductions().is_empty());
+    }
+
+    #[test]
+    fn fifty_fifty_is_even_odds() {
+        let board = fifty_fifty();
+        assert_eq!(board.mine_probability(0, 0), 0.5);
+        assert_eq!(board.mine_probability(0, 1), 0.5);
+        assert_eq!(board.mine_probability(1, 0), 0.);
+    }
+
+    #[test]
+    fn probability_weighs_arrangements_by_the_mines_left_outside() {
+        // `?1?1?` then 5 covered tiles, with 2 mines in total: either the middle tile
+        // holds a mine and the other one is among the 5, or both ends of the pattern do
+        let mut board = played(10, 1, &[(2, 0), (9, 0)], (1, 0));
+        board.handle_primary_action(3, 0);
+
+        let probability = board.mine_probability(0, 0);
+        assert!((probability - 1. / 6.).abs() < 1e-6, "{probability}");
+        let probability = board.mine_probability(2, 0);
+        assert!((probability - 5. / 6.).abs() < 1e-6, "{probability}");
+    }
+
+    #[test]
+    fn hint_prefers_safe_tiles_then_unflagged_mines() {
+        let mut board = no_guess();
+        assert_eq!(board.hint(), Some(Hint::Safe((3, 0))));
+
+        // a flagged safe tile cannot be uncovered as is
+        board.handle_secondary_action(3, 0);
+        assert_eq!(board.hint(), Some(Hint::Mine((2, 0))));
+    }
+
+    #[test]
+    fn hint_guesses_the_least_likely_mine() {
+        assert!(matches!(
+            fifty_fifty().hint(),
+            Some(Hint::Guess((0, 0) | (0, 1)))
+        ));
+
+        // the ends of `?1?1?` are safer than the tiles away from the hints
+        let mut board = played(10, 1, &[(2, 0), (9, 0)], (1, 0));
+        board.handle_primary_action(3, 0);
+        assert_eq!(board.hint(), Some(Hint::Guess((0, 0))));
+    }
+
+    #[test]
+    fn solvability_tells_deductions_from_guesses() {
+        assert!(no_guess().is_solvable_without_guessing());
+        assert!(!fifty_fifty().is_solvable_without_guessing());
+    }
+}
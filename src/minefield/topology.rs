@@ -0,0 +1,448 @@
+use std::fmt;
+
+/// How the tiles of a board connect to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// A plain rectangle, tiles on the edges simply have fewer neighbors.
+    Bounded,
+
+    /// Opposite edges are adjacent, like on the surface of a torus.
+    ///
+    /// Every tile has the full set of neighbors, wrapping around the edges.
+    Toroidal,
+
+    /// Hexagonal tiles with six neighbors each, in rows where every odd row
+    /// is shifted right by half a tile.
+    Hex,
+
+    /// A stack of the given number of square layers, where every tile touches
+    /// the 26 tiles of the 3×3×3 cube around it.
+    ///
+    /// Layers are stacked vertically in board coordinates, so layer `z` occupies
+    /// rows `z * height..(z + 1) * height` of the board.
+    ///
+    /// There is always at least one layer, see [Board::new](super::Board::new).
+    Layered(usize),
+}
+
+/// Which tiles around a square tile count as its neighbors,
+/// for hints, flood fill and exploring around hints alike.
+///
+/// Hex tiles always have their six sides as neighbors, and layered tiles
+/// their whole surrounding cube, regardless of this rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The 4 orthogonally adjacent tiles.
+    VonNeumann,
+
+    /// The 8 surrounding tiles, the classic rule.
+    Moore,
+
+    /// The 24 tiles within two steps in any direction.
+    Radius2,
+
+    /// The 8 tiles a chess knight can jump to.
+    Knight,
+}
+
+impl Neighborhood {
+    /// The neighborhood written as by its [Display](fmt::Display) implementation.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "von-neumann" => Some(Self::VonNeumann),
+            "moore" => Some(Self::Moore),
+            "radius-2" => Some(Self::Radius2),
+            "knight" => Some(Self::Knight),
+            _ => None,
+        }
+    }
+
+    pub fn offsets(self) -> Vec<(i32, i32)> {
+        let square = |radius: i32| {
+            (-radius..=radius)
+                .flat_map(move |off_x| (-radius..=radius).map(move |off_y| (off_x, off_y)))
+        };
+
+        match self {
+            Self::VonNeumann => square(1)
+                .filter(|&(off_x, off_y)| off_x.abs() + off_y.abs() == 1)
+                .collect(),
+            Self::Moore => square(1).filter(|&offset| offset != (0, 0)).collect(),
+            Self::Radius2 => square(2).filter(|&offset| offset != (0, 0)).collect(),
+            Self::Knight => square(2)
+                .filter(|&(off_x, off_y)| off_x.abs() * off_y.abs() == 2)
+                .collect(),
+        }
+    }
+}
+
+/// Board row of a row within a layer, with layers of `layer_height` rows
+/// stacked vertically, see [Topology::Layered].
+pub fn stack_row(layer_height: usize, layer: usize, row: usize) -> usize {
+    layer * layer_height + row
+}
+
+/// Layer of a board row and its row within that layer, the inverse of [stack_row].
+pub fn unstack_row(layer_height: usize, y: usize) -> (usize, usize) {
+    (y / layer_height, y % layer_height)
+}
+
+const HEX_EVEN_ROW: [(i32, i32); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+const HEX_ODD_ROW: [(i32, i32); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+impl Topology {
+    /// Number of layers the board is made of, 1 for flat boards.
+    pub fn layers(self) -> usize {
+        match self {
+            Self::Layered(depth) => depth,
+            Self::Bounded | Self::Toroidal | Self::Hex => 1,
+        }
+    }
+
+    /// Tiles adjacent to `(x, y)` on a board of the given dimensions.
+    pub fn neighbors(
+        self,
+        neighborhood: Neighborhood,
+        dims: (usize, usize),
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let offsets = match self {
+            Self::Bounded | Self::Toroidal => neighborhood.offsets(),
+            Self::Hex if y % 2 == 0 => HEX_EVEN_ROW.to_vec(),
+            Self::Hex => HEX_ODD_ROW.to_vec(),
+            Self::Layered(_) => Neighborhood::Moore.offsets(),
+        };
+        let layer_offsets = match self {
+            Self::Layered(_) => -1..=1,
+            Self::Bounded | Self::Toroidal | Self::Hex => 0..=0,
+        };
+
+        // the tiles right above and below only count on layered boards,
+        // and the tile itself is dropped below along with duplicates
+        let mut neighbors: Vec<_> = layer_offsets
+            .flat_map(|off_z| {
+                offsets
+                    .iter()
+                    .chain([&(0, 0)])
+                    .map(move |&(off_x, off_y)| (off_x, off_y, off_z))
+            })
+            .filter_map(|offset| self.offset(dims, (x, y), offset))
+            .collect();
+
+        // on very narrow boards, wrapping around reaches the same tile from both sides
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        neighbors.retain(|&pos| pos != (x, y));
+
+        neighbors.into_iter()
+    }
+
+    fn offset(
+        self,
+        (width, height): (usize, usize),
+        (x, y): (usize, usize),
+        (off_x, off_y, off_z): (i32, i32, i32),
+    ) -> Option<(usize, usize)> {
+        let new_x = x as i32 + off_x;
+        let new_y = y as i32 + off_y;
+
+        match self {
+            Self::Bounded | Self::Hex => {
+                let x_in_bounds = new_x >= 0 && new_x < width as i32;
+                let y_in_bounds = new_y >= 0 && new_y < height as i32;
+
+                if !x_in_bounds || !y_in_bounds {
+                    return None;
+                }
+
+                Some((new_x as _, new_y as _))
+            }
+            Self::Toroidal => Some((
+                new_x.rem_euclid(width as i32) as _,
+                new_y.rem_euclid(height as i32) as _,
+            )),
+            Self::Layered(depth) => {
+                let layer_height = height / depth;
+                let (layer, row) = unstack_row(layer_height, y);
+                let new_row = row as i32 + off_y;
+                let new_layer = layer as i32 + off_z;
+
+                let x_in_bounds = new_x >= 0 && new_x < width as i32;
+                let row_in_bounds = new_row >= 0 && new_row < layer_height as i32;
+                let layer_in_bounds = new_layer >= 0 && new_layer < depth as i32;
+
+                if !x_in_bounds || !row_in_bounds || !layer_in_bounds {
+                    return None;
+                }
+
+                let new_y = stack_row(layer_height, new_layer as _, new_row as _);
+                Some((new_x as _, new_y))
+            }
+        }
+    }
+
+    /// Number of rings of tiles between two tiles, as seen on the screen.
+    pub fn distance(
+        self,
+        (width, height): (usize, usize),
+        (from_x, from_y): (usize, usize),
+        (to_x, to_y): (usize, usize),
+    ) -> usize {
+        let x_projection = usize::abs_diff(from_x, to_x);
+        let y_projection = usize::abs_diff(from_y, to_y);
+
+        match self {
+            Self::Bounded => std::cmp::max(x_projection, y_projection),
+            Self::Toroidal => std::cmp::max(
+                std::cmp::min(x_projection, width - x_projection),
+                std::cmp::min(y_projection, height - y_projection),
+            ),
+            Self::Hex => {
+                // odd-row offset coordinates to axial coordinates
+                let axial =
+                    |x: usize, y: usize| (x as i64 - (y as i64 - (y as i64 & 1)) / 2, y as i64);
+                let (from_q, from_r) = axial(from_x, from_y);
+                let (to_q, to_r) = axial(to_x, to_y);
+                let (diff_q, diff_r) = (from_q - to_q, from_r - to_r);
+
+                ((diff_q.abs() + diff_r.abs() + (diff_q + diff_r).abs()) / 2) as _
+            }
+            Self::Layered(depth) => {
+                let layer_height = height / depth;
+                let row_projection = usize::abs_diff(from_y % layer_height, to_y % layer_height);
+                let layer_projection = usize::abs_diff(from_y / layer_height, to_y / layer_height);
+
+                x_projection.max(row_projection).max(layer_projection)
+            }
+        }
+    }
+
+    /// The topology written as by its [Display](fmt::Display) implementation.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "bounded" => Some(Self::Bounded),
+            "toroidal" => Some(Self::Toroidal),
+            "hex" => Some(Self::Hex),
+            _ => text
+                .strip_prefix("layered ")?
+                .parse()
+                .ok()
+                .filter(|&depth| depth > 0)
+                .map(Self::Layered),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bounded => write!(f, "bounded"),
+            Self::Toroidal => write!(f, "toroidal"),
+            Self::Hex => write!(f, "hex"),
+            Self::Layered(depth) => write!(f, "layered {depth}"),
+        }
+    }
+}
+
+impl fmt::Display for Neighborhood {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::VonNeumann => "von-neumann",
+            Self::Moore => "moore",
+            Self::Radius2 => "radius-2",
+            Self::Knight => "knight",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layered_neighbors_span_the_adjacent_layers() {
+        let topology = Topology::Layered(3);
+        let dims = (5, 5 * 3);
+        let center = (2, stack_row(5, 1, 2));
+
+        let neighbors: Vec<_> = topology
+            .neighbors(Neighborhood::Moore, dims, center)
+            .collect();
+        assert_eq!(neighbors.len(), 26);
+
+        let layers: Vec<_> = neighbors
+            .iter()
+            .map(|&(_, y)| unstack_row(5, y).0)
+            .collect();
+        for layer in 0..3 {
+            assert_eq!(
+                layers.iter().filter(|&&l| l == layer).count(),
+                9 - (layer == 1) as usize
+            );
+        }
+    }
+
+    #[test]
+    fn layered_neighbors_stop_at_layer_edges() {
+        let topology = Topology::Layered(2);
+        let corner = (0, stack_row(4, 1, 0));
+
+        for (_, y) in topology.neighbors(Neighborhood::Moore, (4, 8), corner) {
+            let (_, row) = unstack_row(4, y);
+            assert!(row <= 1, "row {row} wrapped around the layer");
+        }
+        assert_eq!(
+            topology
+                .neighbors(Neighborhood::Moore, (4, 8), corner)
+                .count(),
+            7
+        );
+    }
+
+    #[test]
+    fn parse_rejects_a_board_without_layers() {
+        assert_eq!(Topology::parse("layered 2"), Some(Topology::Layered(2)));
+        assert_eq!(Topology::parse("layered 0"), None);
+    }
+
+    fn neighbors(
+        topology: Topology,
+        neighborhood: Neighborhood,
+        dims: (usize, usize),
+        pos: (usize, usize),
+    ) -> Vec<(usize, usize)> {
+        topology.neighbors(neighborhood, dims, pos).collect()
+    }
+
+    #[test]
+    fn toroidal_corners_wrap_both_ways() {
+        assert_eq!(
+            neighbors(Topology::Toroidal, Neighborhood::Moore, (5, 4), (0, 0)),
+            [
+                (0, 1),
+                (0, 3),
+                (1, 0),
+                (1, 1),
+                (1, 3),
+                (4, 0),
+                (4, 1),
+                (4, 3)
+            ]
+        );
+        assert_eq!(
+            neighbors(Topology::Toroidal, Neighborhood::Moore, (5, 4), (4, 3)),
+            [
+                (0, 0),
+                (0, 2),
+                (0, 3),
+                (3, 0),
+                (3, 2),
+                (3, 3),
+                (4, 0),
+                (4, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn toroidal_edges_wrap_to_the_opposite_edge() {
+        assert_eq!(
+            neighbors(Topology::Toroidal, Neighborhood::Moore, (5, 4), (2, 3)),
+            [
+                (1, 0),
+                (1, 2),
+                (1, 3),
+                (2, 0),
+                (2, 2),
+                (3, 0),
+                (3, 2),
+                (3, 3)
+            ]
+        );
+        assert_eq!(Topology::Toroidal.distance((5, 4), (0, 1), (4, 1)), 1);
+    }
+
+    #[test]
+    fn toroidal_narrow_boards_count_each_neighbor_once() {
+        assert_eq!(
+            neighbors(Topology::Toroidal, Neighborhood::Moore, (2, 2), (0, 0)),
+            [(0, 1), (1, 0), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn hex_rows_alternate_their_neighbors() {
+        assert_eq!(
+            neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (2, 2)),
+            [(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 2)]
+        );
+        assert_eq!(
+            neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (2, 1)),
+            [(1, 1), (2, 0), (2, 2), (3, 0), (3, 1), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn hex_edges_drop_missing_neighbors() {
+        assert_eq!(
+            neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (0, 2)),
+            [(0, 1), (0, 3), (1, 2)]
+        );
+        assert_eq!(
+            neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (4, 1)),
+            [(3, 1), (4, 0), (4, 2)]
+        );
+    }
+
+    #[test]
+    fn hex_ignores_the_neighborhood_rule() {
+        for neighborhood in [Neighborhood::VonNeumann, Neighborhood::Knight] {
+            assert_eq!(
+                neighbors(Topology::Hex, neighborhood, (5, 5), (2, 1)),
+                neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (2, 1))
+            );
+        }
+        for pos in neighbors(Topology::Hex, Neighborhood::Moore, (5, 5), (2, 1)) {
+            assert_eq!(Topology::Hex.distance((5, 5), (2, 1), pos), 1);
+        }
+    }
+
+    #[test]
+    fn neighborhood_offsets_have_the_expected_shape() {
+        let mut von_neumann = Neighborhood::VonNeumann.offsets();
+        von_neumann.sort_unstable();
+        assert_eq!(von_neumann, [(-1, 0), (0, -1), (0, 1), (1, 0)]);
+
+        let knight = Neighborhood::Knight.offsets();
+        assert_eq!(knight.len(), 8);
+        assert!(knight
+            .iter()
+            .all(|&(off_x, off_y)| off_x.abs() * off_y.abs() == 2));
+
+        for neighborhood in [Neighborhood::Moore, Neighborhood::Radius2] {
+            assert!(!neighborhood.offsets().contains(&(0, 0)));
+        }
+    }
+
+    #[test]
+    fn neighborhood_counts_on_interior_and_edge_tiles() {
+        // interior, left edge, corner
+        let expected = [
+            (Neighborhood::VonNeumann, [4, 3, 2]),
+            (Neighborhood::Moore, [8, 5, 3]),
+            (Neighborhood::Radius2, [24, 14, 8]),
+            (Neighborhood::Knight, [8, 4, 2]),
+        ];
+
+        for (neighborhood, counts) in expected {
+            for (pos, count) in [(3, 3), (0, 3), (0, 0)].into_iter().zip(counts) {
+                assert_eq!(
+                    neighbors(Topology::Bounded, neighborhood, (7, 7), pos).len(),
+                    count,
+                    "{neighborhood:?} at {pos:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use crate::minefield::{Board, FirstClick, Neighborhood, Params, Topology};
+
+/// The puzzles shipped with the game.
+pub const BUILTIN_PACK: &str = include_str!("../assets/puzzles.txt");
+
+/// Where puzzles made in the editor are kept, relative to the working directory.
+pub const CUSTOM_PACK_PATH: &str = "custom_puzzles.txt";
+
+/// Highest difficulty rating a puzzle can have, the lowest being 1.
+pub const MAX_DIFFICULTY: u8 = 5;
+
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub title: String,
+
+    /// The pack the puzzle belongs to, or is headed for once made in the editor.
+    pub pack: Pack,
+
+    /// From 1 to [MAX_DIFFICULTY].
+    pub difficulty: u8,
+
+    width: usize,
+    height: usize,
+    mines: Vec<(usize, usize)>,
+    uncovered: Vec<(usize, usize)>,
+}
+
+/// Where a puzzle comes from, as puzzles of different packs may share a title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pack {
+    /// See [BUILTIN_PACK].
+    Builtin,
+    /// See [CUSTOM_PACK_PATH].
+    Custom,
+}
+
+impl Puzzle {
+    /// An empty puzzle for the custom pack, without mines or uncovered tiles.
+    pub fn new(title: String, difficulty: u8, width: usize, height: usize) -> Self {
+        Self {
+            title,
+            pack: Pack::Custom,
+            difficulty,
+            width,
+            height,
+            mines: vec![],
+            uncovered: vec![],
+        }
+    }
+
+    pub fn mines(&self) -> &[(usize, usize)] {
+        &self.mines
+    }
+
+    pub fn is_uncovered(&self, x: usize, y: usize) -> bool {
+        self.uncovered.contains(&(x, y))
+    }
+
+    /// Add or remove a mine, tiles uncovered from the start stay mine-free.
+    pub fn toggle_mine(&mut self, x: usize, y: usize) {
+        if self.is_uncovered(x, y) {
+            return;
+        }
+
+        toggle(&mut self.mines, (x, y));
+    }
+
+    /// Make a tile start uncovered or not, mined tiles always start covered.
+    pub fn toggle_uncovered(&mut self, x: usize, y: usize) {
+        if self.mines.contains(&(x, y)) {
+            return;
+        }
+
+        toggle(&mut self.uncovered, (x, y));
+    }
+
+    /// Parameters of a board fitting the puzzle.
+    pub fn params(&self) -> Params {
+        Params {
+            width: self.width,
+            height: self.height,
+            mines: self.mines.len(),
+            first_click: FirstClick::Safe,
+            topology: Topology::Bounded,
+            neighborhood: Neighborhood::Moore,
+            mines_per_tile: 1,
+        }
+    }
+
+    /// Start over on the given board with this puzzle, keeping the rule options.
+    pub fn load(&self, board: &mut Board) {
+        board.set_params(self.params());
+        board.place_layout(&self.mines);
+
+        for &(x, y) in &self.uncovered {
+            board.handle_primary_action(x, y);
+        }
+    }
+}
+
+/// Puzzles made in the editor, see [CUSTOM_PACK_PATH].
+///
+/// A missing or malformed pack yields no puzzles,
+/// so that a hand-edited file never keeps the game from starting.
+pub fn load_custom_pack() -> Vec<Puzzle> {
+    fs::read_to_string(CUSTOM_PACK_PATH)
+        .ok()
+        .and_then(|source| parse_pack(&source, Pack::Custom).ok())
+        .unwrap_or_default()
+}
+
+/// Append a puzzle to the pack at [CUSTOM_PACK_PATH], creating it if needed.
+pub fn save_to_custom_pack(puzzle: &Puzzle) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(CUSTOM_PACK_PATH)?;
+
+    writeln!(file, "\n{puzzle}")
+}
+
+impl Pack {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Builtin => "builtin",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// The pack called `name`, see [Pack::name].
+    pub fn parse(name: &str) -> Option<Self> {
+        [Self::Builtin, Self::Custom]
+            .into_iter()
+            .find(|pack| pack.name() == name)
+    }
+}
+
+fn toggle(positions: &mut Vec<(usize, usize)>, pos: (usize, usize)) {
+    match positions.iter().position(|&other| other == pos) {
+        Some(idx) => {
+            positions.swap_remove(idx);
+        }
+        None => positions.push(pos),
+    }
+}
+
+/// Writes the puzzle in the pack format, see [parse_pack].
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "title: {}", self.title)?;
+        writeln!(f, "difficulty: {}", self.difficulty)?;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let tile = if self.mines.contains(&(x, y)) {
+                    '*'
+                } else if self.is_uncovered(x, y) {
+                    'o'
+                } else {
+                    '.'
+                };
+                write!(f, "{tile}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Why a puzzle pack failed to parse, and on which line (starting from 1).
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// Read all the puzzles of the given pack.
+///
+/// Every puzzle is a `title:` line, a `difficulty:` line and the rows of the board,
+/// one character per tile:
+///
+/// - `.` a covered tile without a mine
+/// - `*` a covered tile with a mine
+/// - `o` a tile uncovered from the start
+///
+/// Puzzles are separated by blank lines, and lines starting with `#` are comments.
+///
+/// ```text
+/// title: First steps
+/// difficulty: 1
+/// ..*.
+/// o...
+/// ...*
+/// ```
+pub fn parse_pack(source: &str, pack: Pack) -> Result<Vec<Puzzle>, ParseError> {
+    let mut puzzles = vec![];
+    let mut lines = source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.starts_with('#'))
+        .peekable();
+
+    loop {
+        // skip the blank lines between puzzles
+        while lines.next_if(|(_, line)| line.is_empty()).is_some() {}
+
+        let Some((line, title)) = lines.next() else {
+            break;
+        };
+        let title = title
+            .strip_prefix("title:")
+            .ok_or(ParseError {
+                line,
+                reason: "expected a `title:` line",
+            })?
+            .trim()
+            .to_owned();
+
+        let (line, difficulty) = lines.next().ok_or(ParseError {
+            line,
+            reason: "expected a `difficulty:` line",
+        })?;
+        let difficulty = difficulty
+            .strip_prefix("difficulty:")
+            .and_then(|rating| rating.trim().parse().ok())
+            .filter(|rating| (1..=MAX_DIFFICULTY).contains(rating))
+            .ok_or(ParseError {
+                line,
+                reason: "expected a `difficulty:` line, rated from 1 to 5",
+            })?;
+
+        let mut puzzle = Puzzle {
+            title,
+            pack,
+            difficulty,
+            width: 0,
+            height: 0,
+            mines: vec![],
+            uncovered: vec![],
+        };
+
+        while let Some((line, row)) = lines.next_if(|(_, line)| !line.is_empty()) {
+            if puzzle.height > 0 && row.chars().count() != puzzle.width {
+                return Err(ParseError {
+                    line,
+                    reason: "all rows of a puzzle must be equally long",
+                });
+            }
+
+            let y = puzzle.height;
+            for (x, tile) in row.chars().enumerate() {
+                match tile {
+                    '.' => (),
+                    '*' => puzzle.mines.push((x, y)),
+                    'o' => puzzle.uncovered.push((x, y)),
+                    _ => {
+                        return Err(ParseError {
+                            line,
+                            reason: "unknown tile, expected `.`, `*` or `o`",
+                        })
+                    }
+                }
+            }
+
+            puzzle.width = row.chars().count();
+            puzzle.height += 1;
+        }
+
+        if puzzle.height == 0 {
+            return Err(ParseError {
+                line,
+                reason: "a puzzle needs at least one row",
+            });
+        }
+
+        puzzles.push(puzzle);
+    }
+
+    Ok(puzzles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(source: &str) -> (usize, &'static str) {
+        let err = parse_pack(source, Pack::Custom).expect_err("malformed pack parsed");
+        (err.line, err.reason)
+    }
+
+    #[test]
+    fn parses_a_well_formed_pack() {
+        let source = "\
+# two puzzles
+title: First
+difficulty: 1
+..*.
+o...
+
+# a comment between puzzles
+
+
+title:   Second
+difficulty: 5
+*o
+";
+        let puzzles = parse_pack(source, Pack::Custom).unwrap();
+        assert_eq!(puzzles.len(), 2);
+
+        let first = &puzzles[0];
+        assert_eq!((first.title.as_str(), first.difficulty), ("First", 1));
+        assert_eq!((first.width, first.height), (4, 2));
+        assert_eq!(first.mines, [(2, 0)]);
+        assert_eq!(first.uncovered, [(0, 1)]);
+
+        let second = &puzzles[1];
+        assert_eq!((second.title.as_str(), second.difficulty), ("Second", 5));
+        assert_eq!(second.mines, [(0, 0)]);
+        assert_eq!(second.uncovered, [(1, 0)]);
+    }
+
+    #[test]
+    fn parses_an_empty_pack() {
+        assert!(parse_pack("", Pack::Custom).unwrap().is_empty());
+        assert!(parse_pack("# nothing yet\n\n", Pack::Custom)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn parses_what_it_writes() {
+        let mut puzzle = Puzzle::new("Round trip".to_owned(), 3, 3, 2);
+        puzzle.toggle_mine(1, 0);
+        puzzle.toggle_uncovered(2, 1);
+
+        let parsed = parse_pack(&puzzle.to_string(), Pack::Custom).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].title, puzzle.title);
+        assert_eq!(parsed[0].params(), puzzle.params());
+        assert_eq!(parsed[0].mines, puzzle.mines);
+        assert_eq!(parsed[0].uncovered, puzzle.uncovered);
+    }
+
+    #[test]
+    fn parses_the_builtin_pack() {
+        let puzzles = parse_pack(BUILTIN_PACK, Pack::Builtin).unwrap();
+        assert!(!puzzles.is_empty());
+        assert!(puzzles.iter().all(|puzzle| puzzle.pack == Pack::Builtin));
+    }
+
+    #[test]
+    fn rejects_a_missing_title() {
+        assert_eq!(
+            parse_error("difficulty: 1\n..\n"),
+            (1, "expected a `title:` line")
+        );
+    }
+
+    #[test]
+    fn rejects_a_missing_difficulty() {
+        assert_eq!(
+            parse_error("title: Cut short"),
+            (1, "expected a `difficulty:` line")
+        );
+        assert_eq!(
+            parse_error("title: Unrated\n..\n"),
+            (2, "expected a `difficulty:` line, rated from 1 to 5")
+        );
+    }
+
+    #[test]
+    fn rejects_an_out_of_range_difficulty() {
+        for rating in ["0", "6", "hard"] {
+            let source = format!("title: Off the scale\ndifficulty: {rating}\n..\n");
+            assert_eq!(
+                parse_error(&source),
+                (2, "expected a `difficulty:` line, rated from 1 to 5")
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert_eq!(
+            parse_error("title: Ragged\ndifficulty: 1\n...\n..\n"),
+            (4, "all rows of a puzzle must be equally long")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_tiles() {
+        assert_eq!(
+            parse_error("title: Typo\ndifficulty: 1\n.x.\n"),
+            (3, "unknown tile, expected `.`, `*` or `o`")
+        );
+    }
+
+    #[test]
+    fn rejects_a_puzzle_without_rows() {
+        assert_eq!(
+            parse_error("title: Empty\ndifficulty: 1\n\ntitle: Next\ndifficulty: 1\n.\n"),
+            (2, "a puzzle needs at least one row")
+        );
+    }
+}
@@ -1,11 +1,29 @@
 use nanorand::{RandomRange, Rng};
 
 pub trait IteratorRandom: Iterator + Sized {
+    /// Choose `n` items out of the iterator in a single pass,
+    /// using reservoir sampling (Algorithm R).
+    ///
+    /// Given an unbiased `rng`, this guarantees that:
+    ///
+    /// - every `n`-item subset is equally likely to be chosen,
+    ///   so each item ends up chosen with probability `n / len`
+    /// - with `n >= len`, all items are returned, in iteration order
+    /// - with `n == 0`, nothing is returned
+    ///
+    /// The order of the chosen items is otherwise unspecified.
+    /// Mine placement relies on this for unbiased layouts on every board size.
+    // kept unsized-friendly even though `Rng` currently implies `Sized` through `Clone`
+    #[allow(clippy::needless_maybe_sized)]
     fn choose_multiple<const OUT: usize, R: Rng<OUT> + ?Sized>(
         mut self,
         rng: &mut R,
         n: usize,
     ) -> Vec<Self::Item> {
+        if n == 0 {
+            return vec![];
+        }
+
         let mut reservoir = Vec::with_capacity(n);
         reservoir.extend(self.by_ref().take(n));
 
@@ -28,3 +46,61 @@ pub trait IteratorRandom: Iterator + Sized {
 }
 
 impl<I: Iterator + Sized> IteratorRandom for I {}
+
+/// SplitMix64 finalizer, spreads nearby inputs like consecutive days
+/// or chunk coordinates into unrelated seeds.
+pub fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nanorand::WyRand;
+
+    #[test]
+    fn choose_multiple_picks_every_index_uniformly() {
+        const LEN: usize = 20;
+        const CHOSEN: usize = 5;
+        const SEEDS: u64 = 4000;
+
+        let mut counts = [0usize; LEN];
+        for seed in 0..SEEDS {
+            let mut rng = WyRand::new_seed(seed);
+            let chosen = (0..LEN).choose_multiple(&mut rng, CHOSEN);
+            assert_eq!(chosen.len(), CHOSEN);
+
+            for index in chosen {
+                counts[index] += 1;
+            }
+        }
+
+        let expected = (SEEDS as usize * CHOSEN) as f64 / LEN as f64;
+        let chi_square: f64 = counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+
+        // critical value for 19 degrees of freedom at p = 0.001
+        assert!(chi_square < 43.82, "chi-square {chi_square} for {counts:?}");
+    }
+
+    #[test]
+    fn choose_multiple_returns_everything_when_asked_for_more() {
+        let mut rng = WyRand::new_seed(0);
+        assert_eq!((0..5).choose_multiple(&mut rng, 5), [0, 1, 2, 3, 4]);
+        assert_eq!((0..5).choose_multiple(&mut rng, 8), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn choose_multiple_returns_nothing_for_zero() {
+        let mut rng = WyRand::new_seed(0);
+        assert!((0..5).choose_multiple(&mut rng, 0).is_empty());
+        assert!(std::iter::empty::<u8>()
+            .choose_multiple(&mut rng, 3)
+            .is_empty());
+    }
+}
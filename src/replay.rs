@@ -0,0 +1,243 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+
+use crate::minefield::{Board, Params};
+use crate::state::Mode;
+
+/// Where saved replays are appended, relative to the working directory.
+pub const REPLAYS_PATH: &str = "replays.txt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Primary,
+    Secondary,
+}
+
+/// A single player action, as the board received it.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    /// Game time at which the action happened.
+    pub time_milisec: u32,
+    pub input: Input,
+    pub pos: (usize, usize),
+}
+
+/// Every action of the game being played, in order.
+#[derive(Debug, Default)]
+pub struct Recording {
+    steps: Vec<Step>,
+    /// Whether the assists acted during the game, which the steps do not account for.
+    assisted: bool,
+}
+
+/// A finished game, with everything needed to play it out again.
+///
+/// Games where the assists acted have no replay, see [Recording::note_assists].
+#[derive(Debug)]
+pub struct Replay<'a> {
+    params: Params,
+    mode: Mode,
+    unsure_marks: bool,
+    /// Whether hints or undos played a part, see [State::is_assisted](crate::state::State::is_assisted).
+    assisted: bool,
+    /// Mined tiles and how many mines each of them holds.
+    mines: Vec<((usize, usize), usize)>,
+    steps: &'a [Step],
+}
+
+impl Recording {
+    pub fn record(&mut self, time_milisec: u32, input: Input, pos: (usize, usize)) {
+        self.steps.push(Step {
+            time_milisec,
+            input,
+            pos,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.assisted = false;
+    }
+
+    /// Remember that the assists acted, so that the steps alone
+    /// no longer play the game out again.
+    pub fn note_assists(&mut self) {
+        self.assisted = true;
+    }
+
+    /// Forget the last step, taken back with an undo.
+    pub fn pop(&mut self) {
+        self.steps.pop();
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl<'a> Replay<'a> {
+    /// The game recorded in `recording`, played on `board` once its mines were placed,
+    /// `None` if the assists took actions the recording is missing.
+    pub fn new(
+        board: &Board,
+        recording: &'a Recording,
+        mode: Mode,
+        assisted: bool,
+    ) -> Option<Self> {
+        if recording.assisted {
+            return None;
+        }
+
+        let (width, height) = board.dims();
+        let mines = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| ((x, y), board.tile(x, y).mines()))
+            .filter(|&(_, mines)| mines > 0)
+            .collect();
+
+        Some(Self {
+            params: board.params(),
+            mode,
+            unsure_marks: board.unsure_marks(),
+            assisted,
+            mines,
+            steps: recording.steps(),
+        })
+    }
+
+    /// Append the replay to the file at [REPLAYS_PATH], creating it if needed.
+    pub fn save(&self) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(REPLAYS_PATH)?;
+
+        writeln!(file, "\n{self}")
+    }
+}
+
+/// Writes the replay as plain text, one step per line:
+///
+/// ```text
+/// size: 9x9
+/// mine count: 10, up to 1 per tile
+/// topology: bounded
+/// neighborhood: moore
+/// first click: safe-area
+/// mode: classic
+/// unsure marks: yes
+/// assisted: no
+/// mines: 0,3 4,4 ..
+/// 0 primary 4,5
+/// 1830 secondary 2,3
+/// ```
+///
+/// Tiles holding several mines are written as `x,y*count`.
+impl fmt::Display for Replay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self.params;
+        let yes_no = |yes| if yes { "yes" } else { "no" };
+
+        writeln!(f, "size: {}x{}", params.width, params.height)?;
+        writeln!(
+            f,
+            "mine count: {}, up to {} per tile",
+            params.mines, params.mines_per_tile
+        )?;
+        writeln!(f, "topology: {}", params.topology)?;
+        writeln!(f, "neighborhood: {}", params.neighborhood)?;
+        writeln!(f, "first click: {}", params.first_click)?;
+        writeln!(f, "mode: {}", self.mode)?;
+        writeln!(f, "unsure marks: {}", yes_no(self.unsure_marks))?;
+        writeln!(f, "assisted: {}", yes_no(self.assisted))?;
+
+        write!(f, "mines:")?;
+        for &((x, y), mines) in &self.mines {
+            match mines {
+                1 => write!(f, " {x},{y}")?,
+                _ => write!(f, " {x},{y}*{mines}")?,
+            }
+        }
+        writeln!(f)?;
+
+        for step in self.steps {
+            let input = match step.input {
+                Input::Primary => "primary",
+                Input::Secondary => "secondary",
+            };
+            let (x, y) = step.pos;
+            writeln!(f, "{} {input} {x},{y}", step.time_milisec)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::BEGINNER;
+
+    #[test]
+    fn writes_params_field_by_field() {
+        let mut board = Board::new(Params {
+            width: 4,
+            height: 2,
+            mines: 3,
+            mines_per_tile: 2,
+            ..BEGINNER
+        });
+        board.place_layout(&[(0, 0), (3, 1), (3, 1)]);
+
+        let mut recording = Recording::default();
+        recording.record(0, Input::Primary, (1, 0));
+        recording.record(1830, Input::Secondary, (3, 1));
+
+        assert_eq!(
+            Replay::new(&board, &recording, Mode::Lives(2), true)
+                .unwrap()
+                .to_string(),
+            "\
+size: 4x2
+mine count: 3, up to 2 per tile
+topology: bounded
+neighborhood: moore
+first click: safe-area
+mode: lives 2
+unsure marks: yes
+assisted: yes
+mines: 0,0 3,1*2
+0 primary 1,0
+1830 secondary 3,1
+"
+        );
+    }
+
+    #[test]
+    fn no_replay_once_the_assists_acted() {
+        let board = Board::new(BEGINNER);
+        let mut recording = Recording::default();
+        recording.record(0, Input::Primary, (1, 0));
+        recording.note_assists();
+        assert!(Replay::new(&board, &recording, Mode::Classic, true).is_none());
+
+        recording.clear();
+        assert!(Replay::new(&board, &recording, Mode::Classic, false).is_some());
+    }
+
+    #[test]
+    fn pop_forgets_the_last_step() {
+        let mut recording = Recording::default();
+        recording.record(0, Input::Primary, (1, 0));
+        recording.record(500, Input::Secondary, (2, 0));
+
+        recording.pop();
+        assert_eq!(recording.steps().len(), 1);
+        assert_eq!(recording.steps()[0].pos, (1, 0));
+
+        recording.pop();
+        recording.pop();
+        assert!(recording.steps().is_empty());
+    }
+}
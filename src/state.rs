@@ -1,22 +1,53 @@
+pub(crate) mod animation;
+pub(crate) mod confirm;
 pub(crate) mod defeat;
-mod paused;
+pub(crate) mod editor;
+pub(crate) mod endless;
+mod mode;
+pub(crate) mod panel;
+pub(crate) mod paused;
 mod playing;
-mod victory;
+mod puzzle_select;
+pub(crate) mod undo;
+pub(crate) mod victory;
 
 use notan::draw::*;
 use notan::prelude::*;
 
-use crate::drawing::TILE_SIZE;
-use crate::minefield::Board;
+use crate::audio::{self, Audio, VOLUME_STEP};
+use crate::daily::{self, Daily, DailyRun, DAILY_PARAMS};
+use crate::drawing::{self, MineCounter, TILE_SIZE};
+use crate::minefield::{Assists, Board, Hint, Params, Topology};
+use crate::puzzle::{self, Pack, Puzzle};
+use crate::replay::{Input, Recording};
+use crate::stats::{Completions, Leaderboard};
 
-use defeat::DefeatState;
+use animation::{Animations, RevealSpeed};
+use confirm::Restart;
+use defeat::PostMortem;
+use editor::EditorState;
+use endless::EndlessState;
+use paused::PauseMenu;
+use undo::Snapshot;
+use victory::VictoryState;
+
+pub use mode::{Mode, PLAYERS};
 
 #[derive(Debug)]
 pub enum Stage {
     Playing,
-    Paused,
-    Victory,
-    Defeat(DefeatState),
+    /// The game on hold with the board hidden, behind the pause menu.
+    Paused(PauseMenu),
+    Victory(VictoryState),
+    Defeat(PostMortem),
+    /// An endless game, played on its own board instead of [State::board].
+    Endless(EndlessState),
+    /// Picking a puzzle from the pack, holds the index of the highlighted one.
+    PuzzleSelect(usize),
+    /// Designing a puzzle, played out on [State::board] to show live hints.
+    Editor(EditorState),
+    /// Asking whether to abandon the game in progress for the given restart.
+    ConfirmRestart(Restart),
 }
 
 #[derive(AppState)]
@@ -24,25 +55,93 @@ pub struct State {
     stage: Stage,
     board: Board,
     hover: Option<(usize, usize)>,
+    /// Last known mouse position on the screen, for the hover states of the side panel.
+    pointer: (f32, f32),
+    /// Layer shown on a [Topology::Layered] board, always 0 on flat boards.
+    layer: usize,
     run_timer_milisec: u32,
+    mine_counter: MineCounter,
+    animations: Animations,
+    reveal_speed: RevealSpeed,
+    audio: Audio,
+    assists: Assists,
+    /// Whether the current run had any assist active, or used a hint or an undo.
+    assisted: bool,
+    /// Tile pointed out by the last hint asked for, until the next action.
+    hint: Option<Hint>,
+    /// The game before each action of the current run, latest last.
+    history: Vec<Snapshot>,
+    /// Whether the current run retries a board already played, see [Record::practice](crate::stats::Record::practice).
+    practice: bool,
+    recording: Recording,
+    mode: Mode,
+    /// Explosions the current game can still survive, see [Mode::Lives].
+    lives_left: u8,
+    /// Boards cleared in a row, see [Mode::Survival].
+    boards_cleared: u32,
+    /// Score of each player, see [Mode::HotSeat].
+    scores: [i32; PLAYERS],
+    /// Index of the player whose turn it is.
+    active_player: usize,
+    leaderboard: Leaderboard,
+    puzzles: Vec<Puzzle>,
+    /// Index of the puzzle being played, `None` for randomly generated boards.
+    puzzle: Option<usize>,
+    completions: Completions,
+    daily: Option<DailyRun>,
     font: Font,
     font_mono: Font,
 }
 
 impl State {
-    pub fn new(font: Font, font_mono: Font) -> Self {
+    pub fn new(font: Font, font_mono: Font, audio: Audio) -> Self {
         Self {
             stage: Stage::Playing,
             board: Board::expert(),
             hover: None,
+            pointer: (0., 0.),
+            layer: 0,
             run_timer_milisec: 0,
+            mine_counter: MineCounter::FlagsOfMines,
+            animations: Animations::default(),
+            reveal_speed: RevealSpeed::Fast,
+            audio,
+            assists: Assists::default(),
+            assisted: false,
+            hint: None,
+            history: vec![],
+            practice: false,
+            recording: Recording::default(),
+            mode: Mode::Classic,
+            lives_left: 0,
+            boards_cleared: 0,
+            scores: [0; PLAYERS],
+            active_player: 0,
+            leaderboard: Leaderboard::load(),
+            puzzles: puzzle::parse_pack(puzzle::BUILTIN_PACK, Pack::Builtin)
+                .unwrap_or_else(|err| panic!("Malformed built-in puzzle pack, {err}"))
+                .into_iter()
+                .chain(puzzle::load_custom_pack())
+                .collect(),
+            puzzle: None,
+            completions: Completions::load(),
+            daily: None,
             font,
             font_mono,
         }
     }
 
     pub fn mouse_to_board_coords(&self, mouse_x: f32, mouse_y: f32) -> Option<(usize, usize)> {
-        let (width, height) = self.board.dims();
+        let (origin_x, origin_y) = drawing::board_origin(self.board.params());
+        let (mouse_x, mouse_y) = (mouse_x - origin_x, mouse_y - origin_y);
+
+        if self.board.params().topology == Topology::Hex {
+            return drawing::hex::screen_to_tile(self.board.dims(), (mouse_x, mouse_y));
+        }
+
+        let params = self.board.params();
+        // only the current layer is on the screen
+        let (width, height) = (params.width, params.height);
         let screen_width = width as f32 * TILE_SIZE;
         let screen_height = height as f32 * TILE_SIZE;
 
@@ -54,7 +153,8 @@ impl State {
         }
 
         let board_x = f32::floor(mouse_x / TILE_SIZE) as usize;
-        let board_y = f32::floor(mouse_y / TILE_SIZE) as usize;
+        let row = f32::floor(mouse_y / TILE_SIZE) as usize;
+        let board_y = params.stacked_row(self.layer, row);
 
         Some((board_x, board_y))
     }
@@ -71,10 +171,110 @@ impl State {
         self.hover
     }
 
+    pub fn animations(&self) -> &Animations {
+        &self.animations
+    }
+
+    pub fn reveal_speed(&self) -> RevealSpeed {
+        self.reveal_speed
+    }
+
+    pub fn audio(&self) -> &Audio {
+        &self.audio
+    }
+
+    pub fn layer(&self) -> usize {
+        self.layer
+    }
+
     pub fn run_timer_milisec(&self) -> u32 {
         self.run_timer_milisec
     }
 
+    pub fn mine_counter(&self) -> MineCounter {
+        self.mine_counter
+    }
+
+    pub fn assists(&self) -> Assists {
+        self.assists
+    }
+
+    /// Whether the current run had any assist active, see [Assists].
+    pub fn is_assisted(&self) -> bool {
+        self.assisted
+    }
+
+    pub fn hint(&self) -> Option<Hint> {
+        self.hint
+    }
+
+    pub fn pointer(&self) -> (f32, f32) {
+        self.pointer
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn lives_left(&self) -> u8 {
+        self.lives_left
+    }
+
+    pub fn boards_cleared(&self) -> u32 {
+        self.boards_cleared
+    }
+
+    pub fn scores(&self) -> [i32; PLAYERS] {
+        self.scores
+    }
+
+    pub fn active_player(&self) -> usize {
+        self.active_player
+    }
+
+    /// The player with the highest score, `None` on a tie.
+    pub fn leading_player(&self) -> Option<usize> {
+        let best = self.scores.iter().max()?;
+        let mut leading = (0..PLAYERS).filter(|&player| self.scores[player] == *best);
+
+        let player = leading.next()?;
+        leading.next().is_none().then_some(player)
+    }
+
+    /// Best time to beat on the current board, from the puzzle completions
+    /// or the pure leaderboard.
+    pub fn best_time(&self) -> Option<u32> {
+        match self.puzzle {
+            Some(idx) => self.completions.best(&self.puzzles[idx]),
+            None => self
+                .leaderboard
+                .pure_best(self.board.params())
+                .map(|record| record.time_milisec),
+        }
+    }
+
+    pub fn puzzles(&self) -> &[Puzzle] {
+        &self.puzzles
+    }
+
+    /// The puzzle being played, if any.
+    pub fn puzzle(&self) -> Option<&Puzzle> {
+        self.puzzle.map(|idx| &self.puzzles[idx])
+    }
+
+    pub fn completions(&self) -> &Completions {
+        &self.completions
+    }
+
+    /// The daily challenge being played, if any.
+    pub fn daily(&self) -> Option<DailyRun> {
+        self.daily
+    }
+
     pub fn font(&self) -> &Font {
         &self.font
     }
@@ -82,6 +282,126 @@ impl State {
     pub fn font_mono(&self) -> &Font {
         &self.font_mono
     }
+
+    /// Replace the board with a fresh one using different parameters,
+    /// resizing the window to fit.
+    fn set_params(&mut self, app: &mut App, params: Params) {
+        self.puzzle = None;
+        self.daily = None;
+        self.board.set_seed(None);
+        self.board.set_params(params);
+        self.layer = 0;
+        self.restart();
+        self.fit_window(app);
+    }
+
+    /// Replace the board with the puzzle at the given index of the pack,
+    /// resizing the window to fit.
+    fn start_puzzle(&mut self, app: &mut App, idx: usize) {
+        self.puzzle = Some(idx);
+        self.daily = None;
+        self.mode = Mode::Classic;
+        self.layer = 0;
+        self.restart();
+        self.fit_window(app);
+    }
+
+    /// Replace the board with today's daily challenge, resizing the window to fit.
+    fn start_daily(&mut self, app: &mut App) {
+        let daily = Daily::today();
+
+        self.puzzle = None;
+        self.mode = Mode::Classic;
+        self.daily = Some(DailyRun {
+            daily,
+            official: false,
+            shared: false,
+        });
+        self.board.set_seed(Some(daily.seed()));
+        self.board.set_params(DAILY_PARAMS);
+        self.layer = 0;
+        self.restart();
+        self.fit_window(app);
+    }
+
+    /// Export the result of the finished daily challenge,
+    /// to the clipboard and to [daily::SHARE_PATH].
+    fn share_daily(&mut self, app: &mut App) {
+        let Some(run) = &mut self.daily else {
+            return;
+        };
+
+        let text = daily::share_text(run.daily, &self.board, self.run_timer_milisec, run.official);
+
+        app.backend.set_clipboard_text(&text);
+        run.shared = std::fs::write(daily::SHARE_PATH, &text).is_ok();
+    }
+
+    fn fit_window(&self, app: &mut App) {
+        let (width, height) = drawing::window_size(self.board.params());
+        app.window().set_size(width as i32, height as i32);
+    }
+
+    fn restart(&mut self) {
+        self.reset_run();
+
+        match self.puzzle {
+            Some(idx) => self.puzzles[idx].load(&mut self.board),
+            None => self.board.reset(),
+        }
+
+        // every attempt at the daily challenge opens on the same tile
+        if let Some(run) = &mut self.daily {
+            run.official = !run.daily.is_attempted() && run.daily.record_attempt().is_ok();
+            run.shared = false;
+
+            // and goes on like any other first action
+            let pos @ (x, y) = run.daily.first_click();
+            let outcome = self.board.handle_primary_action(x, y);
+            self.recording.record(0, Input::Primary, pos);
+            playing::resolve(self, pos, outcome, None);
+        }
+    }
+
+    /// Start over with the mines where they are, see [Board::retry].
+    ///
+    /// Puzzles and the daily challenge start over on the same layout anyway.
+    fn retry_layout(&mut self) {
+        if self.puzzle.is_some() || self.daily.is_some() {
+            self.restart();
+            return;
+        }
+
+        self.reset_run();
+        self.practice = self.board.is_initialized();
+        let outcome = self.board.retry();
+
+        // the first action, taken again by the board, goes on like any other
+        if let Some(pos) = self.board.first_action() {
+            self.recording.record(0, Input::Primary, pos);
+            playing::resolve(self, pos, outcome, None);
+        }
+    }
+
+    /// Forget everything about the current run, except for the board.
+    fn reset_run(&mut self) {
+        self.stage = Stage::Playing;
+        self.animations.clear();
+        self.audio.cancel_scheduled();
+        self.run_timer_milisec = 0;
+        self.assisted = false;
+        self.hint = None;
+        self.history.clear();
+        self.practice = false;
+        self.recording.clear();
+        self.boards_cleared = 0;
+        self.lives_left = match self.mode {
+            Mode::Lives(lives) => lives,
+            Mode::Classic | Mode::Countdown { .. } | Mode::Survival | Mode::HotSeat => 0,
+        };
+        self.scores = [0; PLAYERS];
+        self.active_player = 0;
+    }
 }
 
 pub fn setup(gfx: &mut Graphics) -> State {
@@ -94,17 +414,70 @@ pub fn setup(gfx: &mut Graphics) -> State {
         ))
         .unwrap();
 
-    State::new(font, font_mono)
+    State::new(font, font_mono, Audio::new(audio::default_backend()))
+}
+
+/// Pause a game in progress when the player is likely looking away from it.
+///
+/// Focus loss is only approximated: notan 0.9 has no event for the window losing focus,
+/// so the window being minimised stands in for one, along with the long frame stalls
+/// caught while playing. A window left in the background while still drawn keeps playing.
+pub fn event(state: &mut State, event: Event) {
+    let away = match event {
+        Event::WindowResize { width, height } => width == 0 || height == 0,
+        _ => false,
+    };
+
+    if away && matches!(state.stage, Stage::Playing) && state.board.is_initialized() {
+        state.hover = None;
+        state.stage = Stage::Paused(PauseMenu::new());
+    }
 }
 
 pub fn update(app: &mut App, state: &mut State) {
+    if app.keyboard.was_pressed(KeyCode::C) {
+        state.mine_counter.cycle();
+    }
+
+    let layers = state.board.params().topology.layers();
+    let wheel = app.mouse.wheel_delta.y;
+    if app.keyboard.was_pressed(KeyCode::PageUp) || wheel > 0. {
+        state.layer = state.layer.saturating_sub(1);
+    }
+    if app.keyboard.was_pressed(KeyCode::PageDown) || wheel < 0. {
+        state.layer = usize::min(state.layer + 1, layers - 1);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Key0) {
+        state.audio.toggle_mute();
+    }
+    if app.keyboard.was_pressed(KeyCode::Minus) {
+        state.audio.change_volume(-VOLUME_STEP);
+    }
+    if app.keyboard.was_pressed(KeyCode::Equals) {
+        state.audio.change_volume(VOLUME_STEP);
+    }
+
+    let delta = app.timer.delta().subsec_millis();
+    state.animations.update(delta);
+    state.audio.update(delta);
+
+    state.pointer = app.mouse.position();
+
+    // endless games and the editor have panels of their own
+    let has_panel = !matches!(state.stage, Stage::Endless(_) | Stage::Editor(_));
+    if has_panel && panel::update(app, state) {
+        return;
+    }
+
     match &mut state.stage {
         Stage::Playing => playing::update(app, state),
-        Stage::Paused => paused::update(app, state),
-        Stage::Defeat(defeat_state) => {
-            defeat_state.update(app);
-            defeat::update(app, state);
-        }
-        Stage::Victory => victory::update(app, state),
+        Stage::Paused(_) => paused::update(app, state),
+        Stage::Defeat(_) => defeat::update(app, state),
+        Stage::Victory(_) => victory::update(app, state),
+        Stage::Endless(_) => endless::update(app, state),
+        Stage::PuzzleSelect(_) => puzzle_select::update(app, state),
+        Stage::Editor(_) => editor::update(app, state),
+        Stage::ConfirmRestart(_) => confirm::update(app, state),
     }
 }
@@ -0,0 +1,116 @@
+/// How long a tile takes to uncover once its turn in a reveal wave comes.
+pub const REVEAL_MILISEC: u32 = 120;
+
+/// How long a flag takes to pop in over a mine, see [Effect::Celebration].
+pub const POP_MILISEC: u32 = 250;
+
+/// What a [TileAnimation] shows happening to its tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// The tile was uncovered, it looks covered until the animation starts.
+    Reveal,
+    /// A mine goes off, leaving the tile scorched for good.
+    Explosion,
+    /// A flag pops in over a mine of a won game, and stays there.
+    Celebration,
+}
+
+/// Pace of the wave uncovering a flooded region, see [Effect::Reveal].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevealSpeed {
+    Instant,
+    Fast,
+    Slow,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TileAnimation {
+    pub pos: (usize, usize),
+    pub effect: Effect,
+    /// Time on the [Animations] clock when the animation starts.
+    start_milisec: u32,
+}
+
+/// Every tile animation in progress, timed on a common clock.
+///
+/// Animations are only for show: the board they depict is already in its final state.
+#[derive(Debug, Default)]
+pub struct Animations {
+    clock_milisec: u32,
+    tiles: Vec<TileAnimation>,
+}
+
+impl RevealSpeed {
+    pub fn cycle(&mut self) {
+        *self = match self {
+            RevealSpeed::Instant => RevealSpeed::Fast,
+            RevealSpeed::Fast => RevealSpeed::Slow,
+            RevealSpeed::Slow => RevealSpeed::Instant,
+        };
+    }
+
+    /// Delay between successive steps of a wave, `None` when there is no wave at all.
+    pub fn step_milisec(self) -> Option<u32> {
+        match self {
+            RevealSpeed::Instant => None,
+            RevealSpeed::Fast => Some(15),
+            RevealSpeed::Slow => Some(60),
+        }
+    }
+}
+
+impl Animations {
+    pub fn update(&mut self, delta_milisec: u32) {
+        self.clock_milisec += delta_milisec;
+
+        // finished reveals look just like the tile does, explosions leave their mark
+        let clock = self.clock_milisec;
+        self.tiles.retain(|animation| {
+            animation.effect != Effect::Reveal || clock < animation.start_milisec + REVEAL_MILISEC
+        });
+    }
+
+    /// Animate tiles in a wave, each starting `step_milisec` later per step away from the first.
+    pub fn start_wave(
+        &mut self,
+        effect: Effect,
+        steps: impl IntoIterator<Item = ((usize, usize), usize)>,
+        step_milisec: u32,
+    ) {
+        let clock = self.clock_milisec;
+        self.tiles
+            .extend(steps.into_iter().map(|(pos, step)| TileAnimation {
+                pos,
+                effect,
+                start_milisec: clock + step as u32 * step_milisec,
+            }));
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    pub fn with_effect(&self, effect: Effect) -> impl Iterator<Item = &TileAnimation> {
+        self.tiles
+            .iter()
+            .filter(move |animation| animation.effect == effect)
+    }
+
+    pub fn on_tile(&self, pos: (usize, usize), effect: Effect) -> Option<&TileAnimation> {
+        self.with_effect(effect)
+            .find(|animation| animation.pos == pos)
+    }
+
+    /// Whether every animation with the given effect started at least `duration_milisec` ago.
+    pub fn settled(&self, effect: Effect, duration_milisec: u32) -> bool {
+        self.with_effect(effect).all(|animation| {
+            self.elapsed_milisec(animation)
+                .is_some_and(|elapsed| elapsed >= duration_milisec)
+        })
+    }
+
+    /// Time since the animation started, `None` while it waits for its turn.
+    pub fn elapsed_milisec(&self, animation: &TileAnimation) -> Option<u32> {
+        self.clock_milisec.checked_sub(animation.start_milisec)
+    }
+}
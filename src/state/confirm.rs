@@ -0,0 +1,64 @@
+use notan::prelude::*;
+
+use crate::minefield::EXPERT;
+use crate::state::{Stage, State};
+
+/// Time into a game past which abandoning it asks for confirmation first.
+const CONFIRM_AFTER_MILISEC: u32 = 10_000;
+
+/// A way of starting over in the middle of a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restart {
+    /// A fresh board, with the mines placed anew on the first click.
+    NewGame,
+    /// The same board covered up again, with the mines where they were.
+    SameLayout,
+    /// The game left altogether, for a fresh board where the game options can be changed again.
+    Abandon,
+}
+
+/// Start over right away, unless that would throw away a game well under way.
+pub fn request(app: &mut App, state: &mut State, restart: Restart) {
+    let asking = match state.stage {
+        Stage::Playing | Stage::Paused(_) => {
+            state.board.is_initialized() && state.run_timer_milisec >= CONFIRM_AFTER_MILISEC
+        }
+        // asking for the same restart again confirms it
+        Stage::ConfirmRestart(pending) => pending != restart,
+        _ => false,
+    };
+
+    if asking {
+        state.hover = None;
+        state.stage = Stage::ConfirmRestart(restart);
+    } else {
+        perform(app, state, restart);
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    let Stage::ConfirmRestart(restart) = state.stage else {
+        return;
+    };
+
+    if app.keyboard.was_pressed(KeyCode::Y) || app.keyboard.was_pressed(KeyCode::Return) {
+        perform(app, state, restart);
+    } else if app.keyboard.was_pressed(KeyCode::N) || app.keyboard.was_pressed(KeyCode::Escape) {
+        state.stage = Stage::Playing;
+    }
+}
+
+fn perform(app: &mut App, state: &mut State, restart: Restart) {
+    match restart {
+        Restart::NewGame => state.restart(),
+        Restart::SameLayout => state.retry_layout(),
+        Restart::Abandon => {
+            // the special modes are left altogether, for the default board
+            let params = match (state.puzzle, state.daily) {
+                (None, None) => state.board.params(),
+                _ => EXPERT,
+            };
+            state.set_params(app, params);
+        }
+    }
+}
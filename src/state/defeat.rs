@@ -1,23 +1,109 @@
 use notan::prelude::*;
 
-use crate::state::{Stage, State};
+use crate::minefield::{Board, Cover, Mark};
+use crate::state::State;
 
+/// What went wrong in a lost game, to show the player once the explosions are over.
 #[derive(Debug)]
-pub struct Explosion {
-    pub pos: (usize, usize),
-    pub delay: u32,
+pub struct PostMortem {
+    /// Flags placed on tiles with fewer mines than flagged.
+    pub wrong_flags: usize,
+
+    /// Mined tiles left covered without a flag.
+    pub missed_mines: usize,
+
+    /// `None` when the game was not lost to a click, e.g. when the time ran out.
+    pub fatal_click: Option<FatalClick>,
 }
 
-#[derive(Debug)]
-pub struct DefeatState {
-    pub explosions: Vec<Explosion>,
-    pub elapsed_milisec: u32,
+#[derive(Debug, Clone, Copy)]
+pub struct FatalClick {
+    pub verdict: Verdict,
+
+    /// Chance the mine that went off was there, as far as the player could tell.
+    pub mine_probability: f32,
 }
 
-impl DefeatState {
-    pub fn update(&mut self, app: &App) {
-        let delta = app.timer.delta().subsec_millis();
-        self.elapsed_milisec += delta;
+/// Whether the fatal click could have been avoided, as the solver sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Exploring around a hint uncovered a mine, as some flag around it was misplaced.
+    MisplacedFlag,
+
+    /// The hints proved the clicked tile to hold a mine.
+    KnownMine,
+
+    /// The hints proved some other tile safe, no guess was needed yet.
+    NeedlessGuess,
+
+    /// Nothing on the board was provably safe, the click had to be a guess.
+    ForcedGuess,
+
+    /// The click was safe, an assist exploring around a hint then trusted a misplaced flag.
+    AssistMisled,
+}
+
+impl PostMortem {
+    /// Examine the lost game on `board`, with its mines still where they were.
+    pub fn new(board: &Board, fatal_click: Option<FatalClick>) -> Self {
+        let (width, height) = board.dims();
+        let mut wrong_flags = 0;
+        let mut missed_mines = 0;
+
+        for y in 0..height {
+            for x in 0..width {
+                let tile = board.tile(x, y);
+                match tile.cover() {
+                    Cover::Up(Mark::Flag(flags)) if flags as usize > tile.mines() => {
+                        wrong_flags += 1;
+                    }
+                    Cover::Up(Mark::Flag(_)) => (),
+                    Cover::Up(_) if tile.is_mine() => missed_mines += 1,
+                    Cover::Up(_) | Cover::Down => (),
+                }
+            }
+        }
+
+        Self {
+            wrong_flags,
+            missed_mines,
+            fatal_click,
+        }
+    }
+}
+
+impl FatalClick {
+    /// Judge a click on `clicked` that set off the mine on `triggered`,
+    /// from the board as it was right before the click.
+    pub fn judge(before: &Board, clicked: (usize, usize), triggered: (usize, usize)) -> Self {
+        let (x, y) = clicked;
+        let tile = before.tile(x, y);
+        let deductions = before.deductions();
+
+        // a mine going off anywhere else came from exploring around a hint
+        let verdict = if matches!(tile.cover(), Cover::Down) || !tile.is_mine() {
+            Verdict::MisplacedFlag
+        } else if deductions.iter().any(|d| d.pos == clicked && d.mines > 0) {
+            Verdict::KnownMine
+        } else if deductions.iter().any(|d| d.mines == 0) {
+            Verdict::NeedlessGuess
+        } else {
+            Verdict::ForcedGuess
+        };
+
+        Self {
+            verdict,
+            mine_probability: before.mine_probability(triggered.0, triggered.1),
+        }
+    }
+
+    /// Judge a mine set off by the assists rather than by the player,
+    /// from the board as the player's action left it for the assists.
+    pub fn judge_assists(before: &Board, triggered: (usize, usize)) -> Self {
+        Self {
+            verdict: Verdict::AssistMisled,
+            mine_probability: before.mine_probability(triggered.0, triggered.1),
+        }
     }
 }
 
@@ -25,8 +111,89 @@ pub fn update(app: &mut App, state: &mut State) {
     state.hover = None;
 
     if app.keyboard.was_pressed(KeyCode::Space) {
-        state.stage = Stage::Playing;
-        state.run_timer_milisec = 0;
-        state.board.reset();
+        state.restart();
+    }
+
+    if app.keyboard.was_pressed(KeyCode::X) {
+        state.share_daily(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::{Params, Terminal, BEGINNER};
+
+    fn layout(width: usize, height: usize, mines: &[(usize, usize)]) -> Board {
+        let mut board = Board::new(Params {
+            width,
+            height,
+            mines: mines.len(),
+            ..BEGINNER
+        });
+        board.place_layout(mines);
+        board
+    }
+
+    /// Play a fatal primary action on `board` and judge it.
+    fn judge(mut board: Board, clicked: (usize, usize)) -> FatalClick {
+        let before = board.clone();
+        let outcome = board.handle_primary_action(clicked.0, clicked.1);
+        let Some(Terminal::Defeat { triggered }) = outcome.terminal else {
+            panic!("the click at {clicked:?} was not fatal");
+        };
+
+        FatalClick::judge(&before, clicked, triggered)
+    }
+
+    /// ```text
+    /// *1..
+    /// 11..
+    /// ```
+    /// with the mine equally likely on either tile of the left column.
+    fn fifty_fifty() -> Board {
+        let mut board = layout(4, 2, &[(0, 0)]);
+        board.handle_primary_action(3, 1);
+        board
+    }
+
+    #[test]
+    fn clicking_a_proven_mine_is_a_known_mine() {
+        // `o1*.`, the hint leaves a single place for the mine
+        let mut board = layout(4, 1, &[(2, 0)]);
+        board.handle_primary_action(0, 0);
+
+        let fatal_click = judge(board, (2, 0));
+        assert_eq!(fatal_click.verdict, Verdict::KnownMine);
+        assert_eq!(fatal_click.mine_probability, 1.);
+    }
+
+    #[test]
+    fn guessing_with_a_safe_tile_left_is_needless() {
+        // a flag keeps the opening off the far corner, which the mine count proves safe
+        let mut board = layout(8, 2, &[(0, 0)]);
+        board.handle_secondary_action(7, 1);
+        board.handle_primary_action(3, 1);
+
+        let fatal_click = judge(board, (0, 0));
+        assert_eq!(fatal_click.verdict, Verdict::NeedlessGuess);
+        assert_eq!(fatal_click.mine_probability, 0.5);
+    }
+
+    #[test]
+    fn guessing_a_fifty_fifty_is_forced() {
+        let fatal_click = judge(fifty_fifty(), (0, 0));
+        assert_eq!(fatal_click.verdict, Verdict::ForcedGuess);
+        assert_eq!(fatal_click.mine_probability, 0.5);
+    }
+
+    #[test]
+    fn exploring_around_a_wrong_flag_is_a_misplaced_flag() {
+        let mut board = fifty_fifty();
+        board.handle_secondary_action(0, 1);
+
+        let fatal_click = judge(board, (1, 0));
+        assert_eq!(fatal_click.verdict, Verdict::MisplacedFlag);
+        assert_eq!(fatal_click.mine_probability, 0.5);
     }
 }
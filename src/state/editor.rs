@@ -0,0 +1,128 @@
+use notan::prelude::*;
+
+use crate::minefield::Params;
+use crate::puzzle::{self, Pack, Puzzle, MAX_DIFFICULTY};
+use crate::state::{Stage, State};
+
+const DIFFICULTY_KEYS: [KeyCode; MAX_DIFFICULTY as usize] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+];
+
+#[derive(Debug)]
+pub struct EditorState {
+    pub puzzle: Puzzle,
+
+    /// What to go back to when leaving the editor.
+    previous: Previous,
+
+    /// Whether the puzzle can be solved without guessing,
+    /// `None` until the solver runs again after an edit.
+    pub solvable: Option<bool>,
+
+    /// Outcome of the last save, for the side panel.
+    pub status: Option<String>,
+}
+
+/// The game played right before opening the editor.
+#[derive(Debug, Clone, Copy)]
+enum Previous {
+    /// The puzzle at the given index of the pack.
+    Puzzle(usize),
+    /// A random board with the given parameters.
+    Board(Params),
+}
+
+/// Open the editor on a copy of the puzzle being played,
+/// or on an empty puzzle the size of the current board.
+pub fn transition_editor(app: &mut App, state: &mut State) {
+    let (puzzle, previous) = match state.puzzle {
+        Some(idx) => (state.puzzles[idx].clone(), Previous::Puzzle(idx)),
+        None => {
+            let params = state.board.params();
+            let puzzle = Puzzle::new(String::new(), 1, params.width, params.height);
+            (puzzle, Previous::Board(params))
+        }
+    };
+
+    state.puzzle = None;
+    state.daily = None;
+    state.hover = None;
+    state.layer = 0;
+    state.board.set_params(puzzle.params());
+    state.board.place_layout(puzzle.mines());
+    state.fit_window(app);
+
+    state.stage = Stage::Editor(EditorState {
+        puzzle,
+        previous,
+        solvable: None,
+        status: None,
+    });
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let board_coords = state.mouse_to_board_coords(mouse_x, mouse_y);
+    state.hover = board_coords;
+
+    let Stage::Editor(editor) = &mut state.stage else {
+        return;
+    };
+
+    if let Some((x, y)) = board_coords {
+        let edited = if app.mouse.left_was_pressed() {
+            editor.puzzle.toggle_mine(x, y);
+            true
+        } else if app.mouse.right_was_pressed() {
+            editor.puzzle.toggle_uncovered(x, y);
+            true
+        } else {
+            false
+        };
+
+        if edited {
+            editor.solvable = None;
+            editor.status = None;
+            state.board.set_params(editor.puzzle.params());
+            state.board.place_layout(editor.puzzle.mines());
+        }
+    }
+
+    for (rating, key) in (1..).zip(DIFFICULTY_KEYS) {
+        if app.keyboard.was_pressed(key) {
+            editor.puzzle.difficulty = rating;
+        }
+    }
+
+    if app.keyboard.was_pressed(KeyCode::V) {
+        let mut scratch = state.board.clone();
+        editor.puzzle.load(&mut scratch);
+        editor.solvable = Some(scratch.is_solvable_without_guessing());
+    }
+
+    if app.keyboard.was_pressed(KeyCode::S) {
+        let mut puzzle = editor.puzzle.clone();
+        puzzle.title = format!("Custom {}", state.puzzles.len() + 1);
+        puzzle.pack = Pack::Custom;
+
+        editor.status = Some(match puzzle::save_to_custom_pack(&puzzle) {
+            Ok(()) => {
+                let status = format!("saved as {}", puzzle.title);
+                state.puzzles.push(puzzle);
+                status
+            }
+            Err(err) => format!("saving failed: {err}"),
+        });
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Escape) {
+        match editor.previous {
+            Previous::Puzzle(idx) => state.start_puzzle(app, idx),
+            Previous::Board(params) => state.set_params(app, params),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use nanorand::Rng;
+use notan::prelude::*;
+
+use crate::drawing::{self, TILE_SIZE};
+use crate::minefield::{EndlessBoard, CHUNK_SIZE};
+use crate::state::{Stage, State};
+
+/// Fraction of the remaining distance to its target the camera covers per second.
+const CAMERA_FOLLOW_RATE: f32 = 6.;
+/// Manual panning speed, in tiles per second.
+const CAMERA_PAN_SPEED: f32 = 12.;
+
+#[derive(Debug)]
+pub struct EndlessState {
+    pub board: EndlessBoard,
+    /// Center of the view, in tile units.
+    pub camera: (f32, f32),
+    camera_target: (f32, f32),
+    pub hover: Option<(i64, i64)>,
+}
+
+impl EndlessState {
+    /// A fresh endless game, see [EndlessBoard::new].
+    pub fn new(unsure_marks: bool) -> Self {
+        let seed = nanorand::WyRand::new().generate();
+
+        Self {
+            board: EndlessBoard::new(seed, unsure_marks),
+            camera: (0.5, 0.5),
+            camera_target: (0.5, 0.5),
+            hover: None,
+        }
+    }
+
+    /// Tile under a screen position, for a view of the given size.
+    pub fn screen_to_tile(
+        &self,
+        (view_width, view_height): (f32, f32),
+        (screen_x, screen_y): (f32, f32),
+    ) -> (i64, i64) {
+        let (camera_x, camera_y) = self.camera;
+        let x = (screen_x - view_width / 2.) / TILE_SIZE + camera_x;
+        let y = (screen_y - view_height / 2.) / TILE_SIZE + camera_y;
+
+        (x.floor() as i64, y.floor() as i64)
+    }
+
+    /// Screen position of the top-left corner of a tile, for a view of the given size.
+    pub fn tile_to_screen(
+        &self,
+        (view_width, view_height): (f32, f32),
+        (x, y): (i64, i64),
+    ) -> (f32, f32) {
+        let (camera_x, camera_y) = self.camera;
+
+        (
+            (x as f32 - camera_x) * TILE_SIZE + view_width / 2.,
+            (y as f32 - camera_y) * TILE_SIZE + view_height / 2.,
+        )
+    }
+}
+
+pub fn update(app: &mut App, state: &mut State) {
+    if app.keyboard.was_pressed(KeyCode::Escape) {
+        state.restart();
+        return;
+    }
+
+    let view @ (view_width, view_height) = drawing::board_dims(state.board().params());
+    let unsure_marks = state.board.unsure_marks();
+    let Stage::Endless(endless) = &mut state.stage else {
+        return;
+    };
+
+    if endless.board.exploded().is_some() && app.keyboard.was_pressed(KeyCode::Space) {
+        *endless = EndlessState::new(unsure_marks);
+    }
+
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let in_view = (0. ..view_width).contains(&mouse_x) && (0. ..view_height).contains(&mouse_y);
+
+    endless.hover = (in_view && endless.board.exploded().is_none())
+        .then(|| endless.screen_to_tile(view, (mouse_x, mouse_y)));
+
+    if let Some((x, y)) = endless.hover {
+        if app.mouse.left_was_pressed() {
+            endless.board.handle_primary_action(x, y);
+            endless.camera_target = (x as f32 + 0.5, y as f32 + 0.5);
+        } else if app.mouse.right_was_pressed() {
+            endless.board.handle_secondary_action(x, y);
+        }
+    }
+
+    let delta = app.timer.delta_f32();
+    let pan = CAMERA_PAN_SPEED * delta;
+    let (target_x, target_y) = &mut endless.camera_target;
+
+    if app.keyboard.is_down(KeyCode::Left) || app.keyboard.is_down(KeyCode::A) {
+        *target_x -= pan;
+    }
+    if app.keyboard.is_down(KeyCode::Right) || app.keyboard.is_down(KeyCode::D) {
+        *target_x += pan;
+    }
+    if app.keyboard.is_down(KeyCode::Up) || app.keyboard.is_down(KeyCode::W) {
+        *target_y -= pan;
+    }
+    if app.keyboard.is_down(KeyCode::Down) || app.keyboard.is_down(KeyCode::S) {
+        *target_y += pan;
+    }
+
+    let follow = (CAMERA_FOLLOW_RATE * delta).min(1.);
+    let (camera_x, camera_y) = &mut endless.camera;
+    *camera_x += (endless.camera_target.0 - *camera_x) * follow;
+    *camera_y += (endless.camera_target.1 - *camera_y) * follow;
+
+    // keep a chunk of margin around the view, so generation happens before tiles scroll in
+    let view_tiles = view_width.max(view_height) / TILE_SIZE;
+    let radius = (view_tiles / 2. / CHUNK_SIZE as f32).ceil() as i64 + 1;
+    let (camera_x, camera_y) = endless.camera;
+    endless
+        .board
+        .generate_around(camera_x.floor() as i64, camera_y.floor() as i64, radius);
+}
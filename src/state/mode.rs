@@ -0,0 +1,62 @@
+use std::fmt;
+
+/// Number of players taking turns in [Mode::HotSeat].
+pub const PLAYERS: usize = 2;
+
+/// Rules deciding how a game on a generated board ends,
+/// on top of the board's own victory and defeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Clearing the board wins, uncovering a mine loses.
+    Classic,
+
+    /// Like [Mode::Classic], and the game is lost when the time runs out.
+    Countdown { limit_milisec: u32 },
+
+    /// The given number of explosions are survived,
+    /// leaving the mine uncovered and going on.
+    Lives(u8),
+
+    /// Every cleared board is followed by a fresh one, until one is lost.
+    Survival,
+
+    /// [PLAYERS] players take turns on the same board, sharing the flags.
+    ///
+    /// Safe tiles score a point for whoever uncovered them, mines cost points instead
+    /// and are survived. The board ends up cleared, and the highest score wins.
+    HotSeat,
+}
+
+impl Mode {
+    /// The mode written as by its [Display](fmt::Display) implementation.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "classic" => Some(Self::Classic),
+            "survival" => Some(Self::Survival),
+            "hot-seat" => Some(Self::HotSeat),
+            _ => {
+                let (name, value) = text.split_once(' ')?;
+                match name {
+                    "countdown" => value
+                        .parse()
+                        .ok()
+                        .map(|limit_milisec| Self::Countdown { limit_milisec }),
+                    "lives" => value.parse().ok().map(Self::Lives),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Classic => write!(f, "classic"),
+            Self::Countdown { limit_milisec } => write!(f, "countdown {limit_milisec}"),
+            Self::Lives(lives) => write!(f, "lives {lives}"),
+            Self::Survival => write!(f, "survival"),
+            Self::HotSeat => write!(f, "hot-seat"),
+        }
+    }
+}
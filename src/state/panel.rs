@@ -0,0 +1,99 @@
+use notan::prelude::*;
+
+use crate::drawing::{self, widget::Ui};
+use crate::minefield::{Params, BEGINNER, EXPERT, INTERMEDIATE};
+use crate::state::confirm::{self, Restart};
+use crate::state::paused::{Page, PauseMenu};
+use crate::state::undo;
+use crate::state::{Mode, Stage, State};
+
+/// Difficulties the panel cycles through, from easiest to hardest.
+const DIFFICULTIES: [Params; 3] = [BEGINNER, INTERMEDIATE, EXPERT];
+
+/// Something the player asked for from the side panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Restart(Restart),
+    Pause,
+    /// Open the pause menu straight on its settings.
+    Settings,
+    /// Point out a tile to act on, see [Board::hint](crate::minefield::Board::hint).
+    Hint,
+    /// Take back the last action, see [undo].
+    Undo,
+    /// Switch to the next of the standard difficulties.
+    Difficulty,
+    AutoFlag,
+    AutoChord,
+    AutoOpen,
+}
+
+/// Whether the action can be taken at this point of the game.
+pub fn is_enabled(state: &State, action: Action) -> bool {
+    let playing = matches!(state.stage, Stage::Playing);
+
+    match action {
+        Action::Restart(_) => matches!(
+            state.stage,
+            Stage::Playing | Stage::Victory(_) | Stage::Defeat(_) | Stage::ConfirmRestart(_)
+        ),
+        Action::Pause | Action::Settings => playing,
+        // every tile of a hot-seat game is uncovered by one of the players
+        Action::Hint => playing && state.board.is_initialized() && state.mode != Mode::HotSeat,
+        Action::Undo => undo::can_undo(state),
+        // boards of the special modes can be left any time, other games are abandoned with a restart
+        Action::Difficulty => match state.stage {
+            Stage::Playing => {
+                !state.board.is_initialized() || state.puzzle.is_some() || state.daily.is_some()
+            }
+            Stage::Victory(_) | Stage::Defeat(_) => true,
+            _ => false,
+        },
+        Action::AutoFlag | Action::AutoChord | Action::AutoOpen => playing,
+    }
+}
+
+/// Act on the side panel, returning whether it was used this frame.
+pub fn update(app: &mut App, state: &mut State) -> bool {
+    let (board_width, _) = drawing::board_dims(state.board.params());
+    let action = {
+        let mut ui = Ui::input(app, state.font_mono(), (board_width, 0.));
+        drawing::panel::show(&mut ui, state)
+    };
+
+    let Some(action) = action else {
+        return false;
+    };
+
+    match action {
+        Action::Restart(restart) => confirm::request(app, state, restart),
+        Action::Pause => {
+            state.hover = None;
+            state.stage = Stage::Paused(PauseMenu::new());
+        }
+        Action::Settings => {
+            state.hover = None;
+            state.stage = Stage::Paused(PauseMenu {
+                page: Page::Settings,
+                selected: 0,
+            });
+        }
+        Action::Hint => {
+            state.assisted = true;
+            state.hint = state.board.hint();
+        }
+        Action::Undo => undo::undo(state),
+        Action::Difficulty => {
+            let current = DIFFICULTIES
+                .iter()
+                .position(|&params| params == state.board.params());
+            let next = current.map_or(0, |idx| (idx + 1) % DIFFICULTIES.len());
+            state.set_params(app, DIFFICULTIES[next]);
+        }
+        Action::AutoFlag => state.assists.auto_flag = !state.assists.auto_flag,
+        Action::AutoChord => state.assists.auto_chord = !state.assists.auto_chord,
+        Action::AutoOpen => state.assists.auto_open = !state.assists.auto_open,
+    }
+
+    true
+}
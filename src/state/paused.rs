@@ -1,11 +1,134 @@
 use notan::prelude::*;
 
-use crate::state::State;
+use crate::audio::VOLUME_STEP;
+use crate::drawing::paused::item_at;
+use crate::state::confirm::{self, Restart};
+use crate::state::{Stage, State};
 
-use super::Stage;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Main,
+    Settings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Item {
+    Resume,
+    Restart,
+    Settings,
+    /// Abandon the game for a fresh board, where the game options can be changed again.
+    Abandon,
+    RevealSpeed,
+    Mute,
+    VolumeDown,
+    VolumeUp,
+    UnsureMarks,
+    MineCounter,
+    Back,
+}
+
+/// The menu shown over the hidden board while the game is paused.
+#[derive(Debug)]
+pub struct PauseMenu {
+    pub page: Page,
+    /// Index of the highlighted item on the page.
+    pub selected: usize,
+}
+
+impl Page {
+    pub fn items(self) -> &'static [Item] {
+        match self {
+            Page::Main => &[Item::Resume, Item::Restart, Item::Settings, Item::Abandon],
+            Page::Settings => &[
+                Item::RevealSpeed,
+                Item::Mute,
+                Item::VolumeDown,
+                Item::VolumeUp,
+                Item::UnsureMarks,
+                Item::MineCounter,
+                Item::Back,
+            ],
+        }
+    }
+}
+
+impl PauseMenu {
+    pub fn new() -> Self {
+        Self {
+            page: Page::Main,
+            selected: 0,
+        }
+    }
+}
 
 pub fn update(app: &mut App, state: &mut State) {
-    if app.keyboard.was_pressed(KeyCode::Return) {
-        state.stage = Stage::Playing;
+    state.hover = None;
+
+    let Stage::Paused(menu) = &mut state.stage else {
+        return;
+    };
+
+    let items = menu.page.items();
+    let last = items.len() - 1;
+
+    if app.keyboard.was_pressed(KeyCode::Up) || app.keyboard.was_pressed(KeyCode::W) {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if app.keyboard.was_pressed(KeyCode::Down) || app.keyboard.was_pressed(KeyCode::S) {
+        menu.selected = usize::min(menu.selected + 1, last);
+    }
+
+    let (mouse_x, mouse_y) = app.mouse.position();
+    let pointed = item_at(state.board.params(), items.len(), (mouse_x, mouse_y));
+    if let Some(idx) = pointed {
+        menu.selected = idx;
+    }
+
+    let activated = if app.keyboard.was_pressed(KeyCode::Return)
+        || app.keyboard.was_pressed(KeyCode::Space)
+        || (app.mouse.left_was_pressed() && pointed.is_some())
+    {
+        Some(items[menu.selected])
+    } else if app.keyboard.was_pressed(KeyCode::Escape) {
+        match menu.page {
+            Page::Main => Some(Item::Resume),
+            Page::Settings => Some(Item::Back),
+        }
+    } else {
+        None
+    };
+
+    if let Some(item) = activated {
+        activate(app, state, item);
+    }
+}
+
+fn activate(app: &mut App, state: &mut State, item: Item) {
+    match item {
+        Item::Resume => state.stage = Stage::Playing,
+        Item::Restart => confirm::request(app, state, Restart::NewGame),
+        Item::Settings => {
+            state.stage = Stage::Paused(PauseMenu {
+                page: Page::Settings,
+                selected: 0,
+            });
+        }
+        Item::Abandon => confirm::request(app, state, Restart::Abandon),
+        Item::RevealSpeed => state.reveal_speed.cycle(),
+        Item::Mute => state.audio.toggle_mute(),
+        Item::VolumeDown => state.audio.change_volume(-VOLUME_STEP),
+        Item::VolumeUp => state.audio.change_volume(VOLUME_STEP),
+        Item::UnsureMarks => {
+            let unsure_marks = state.board.unsure_marks();
+            state.board.set_unsure_marks(!unsure_marks);
+        }
+        Item::MineCounter => state.mine_counter.cycle(),
+        Item::Back => {
+            let settings = Page::Main.items().iter().position(|&i| i == Item::Settings);
+            state.stage = Stage::Paused(PauseMenu {
+                page: Page::Main,
+                selected: settings.unwrap_or(0),
+            });
+        }
     }
 }
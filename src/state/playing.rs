@@ -1,13 +1,57 @@
 use itertools::Itertools;
 use notan::prelude::*;
 
-use crate::state::defeat::{DefeatState, Explosion};
-use crate::state::{Stage, State};
+use crate::audio::Sound;
+use crate::minefield::{
+    ActionOutcome, Board, Cover, FirstClick, Neighborhood, Object, Reveal, Terminal, Topology,
+    EXPERT,
+};
+use crate::replay::Input;
+use crate::state::animation::Effect;
+use crate::state::defeat::{FatalClick, PostMortem};
+use crate::state::editor;
+use crate::state::endless::EndlessState;
+use crate::state::paused::PauseMenu;
+use crate::state::undo;
+use crate::state::victory::VictoryState;
+use crate::state::{Mode, Stage, State, PLAYERS};
+use crate::stats::Record;
+
+/// Minimum opening size offered when cycling through [FirstClick] policies.
+const FIRST_OPENING_SIZE: usize = 30;
+
+/// Upper bound offered when cycling through [Params::mines_per_tile](crate::minefield::Params::mines_per_tile).
+const MAX_MINES_PER_TILE: u8 = 3;
+
+/// Time limit offered when cycling through [Mode] variants.
+const COUNTDOWN_MILISEC: u32 = 3 * 60_000;
+
+/// Explosions survived in [Mode::Lives], as offered when cycling through [Mode] variants.
+const LIVES: u8 = 3;
+
+/// Points lost for every mine uncovered in [Mode::HotSeat].
+const MINE_PENALTY: i32 = 10;
+
+/// Number of layers offered when cycling through [Topology] variants.
+const LAYERED_DEPTH: usize = 3;
+
+/// Frame time past which the window is assumed to have been hidden or minimised,
+/// e.g. when the platform stops drawing it.
+///
+/// Long enough that a hitch, like a slow file write, does not pause the game.
+const STALL_MILISEC: u128 = 10_000;
 
 pub fn update(app: &mut App, state: &mut State) {
     if state.board().is_initialized() {
-        let delta = app.timer.delta().subsec_millis();
-        state.run_timer_milisec += delta;
+        let delta = app.timer.delta();
+
+        // the time the window was away is not counted
+        if delta.as_millis() >= STALL_MILISEC {
+            state.stage = Stage::Paused(PauseMenu::new());
+            return;
+        }
+
+        state.run_timer_milisec += delta.as_millis() as u32;
     }
 
     let (mouse_x, mouse_y) = app.mouse.position();
@@ -15,72 +59,353 @@ pub fn update(app: &mut App, state: &mut State) {
 
     state.hover = board_coords;
 
-    if let Some((x, y)) = board_coords {
-        if app.mouse.left_was_pressed() {
-            state.board.handle_primary_action(x, y);
-        } else if app.mouse.right_was_pressed() {
-            state.board.handle_secondary_action(x, y);
+    let input = match board_coords {
+        Some(pos) if app.mouse.left_was_pressed() => Some((pos, Input::Primary)),
+        Some(pos) if app.mouse.right_was_pressed() => Some((pos, Input::Secondary)),
+        _ => None,
+    };
+
+    if let Some((pos, input)) = input {
+        act(state, pos, input);
+    }
+
+    if let Mode::Countdown { limit_milisec } = state.mode {
+        if matches!(state.stage, Stage::Playing) && state.run_timer_milisec >= limit_milisec {
+            transition_timeout(state, limit_milisec);
+        }
+    }
+
+    if app.keyboard.was_pressed(KeyCode::F) && !state.board.is_initialized() {
+        let mut params = state.board.params();
+        params.first_click = match params.first_click {
+            FirstClick::Safe => FirstClick::SafeArea,
+            FirstClick::SafeArea => FirstClick::Opening(FIRST_OPENING_SIZE),
+            FirstClick::Opening(_) => FirstClick::Safe,
+        };
+        state.set_params(app, params);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::T) && !state.board.is_initialized() {
+        let mut params = state.board.params();
+        params.topology = match params.topology {
+            Topology::Bounded => Topology::Toroidal,
+            Topology::Toroidal => Topology::Hex,
+            Topology::Hex => Topology::Layered(LAYERED_DEPTH),
+            Topology::Layered(_) => Topology::Bounded,
+        };
+        state.set_params(app, params);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::N) && !state.board.is_initialized() {
+        let mut params = state.board.params();
+        params.neighborhood = match params.neighborhood {
+            Neighborhood::VonNeumann => Neighborhood::Moore,
+            Neighborhood::Moore => Neighborhood::Radius2,
+            Neighborhood::Radius2 => Neighborhood::Knight,
+            Neighborhood::Knight => Neighborhood::VonNeumann,
+        };
+        state.set_params(app, params);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::M) && !state.board.is_initialized() {
+        let mut params = state.board.params();
+        params.mines_per_tile = params.mines_per_tile % MAX_MINES_PER_TILE + 1;
+        state.set_params(app, params);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::E) && !state.board.is_initialized() {
+        state.hover = None;
+        state.stage = Stage::Endless(EndlessState::new(state.board.unsure_marks()));
+        return;
+    }
+
+    // boards of the special modes are set up front, and can be left any time
+    let can_switch_mode =
+        !state.board.is_initialized() || state.puzzle.is_some() || state.daily.is_some();
+
+    if app.keyboard.was_pressed(KeyCode::P) && can_switch_mode {
+        state.hover = None;
+        state.stage = Stage::PuzzleSelect(state.puzzle.unwrap_or(0));
+        return;
+    }
+
+    if app.keyboard.was_pressed(KeyCode::B) && can_switch_mode {
+        editor::transition_editor(app, state);
+        return;
+    }
+
+    if app.keyboard.was_pressed(KeyCode::D) && can_switch_mode {
+        match state.daily {
+            Some(_) => state.set_params(app, EXPERT),
+            None => state.start_daily(app),
         }
+        return;
     }
 
-    if state.board.is_defeat() {
-        let triggered_pos = board_coords
-            .expect("Failed to obtain board coords when transitioning playing -> defeat");
-        transition_defeat(state, triggered_pos);
-    } else if state.board.is_victory() {
-        state.stage = Stage::Victory;
+    if app.keyboard.was_pressed(KeyCode::G) && !state.board.is_initialized() {
+        state.mode = match state.mode {
+            Mode::Classic => Mode::Countdown {
+                limit_milisec: COUNTDOWN_MILISEC,
+            },
+            Mode::Countdown { .. } => Mode::Lives(LIVES),
+            Mode::Lives(_) => Mode::Survival,
+            Mode::Survival => Mode::HotSeat,
+            Mode::HotSeat => Mode::Classic,
+        };
+        state.restart();
+    }
+
+    if app.keyboard.was_pressed(KeyCode::A) {
+        state.reveal_speed.cycle();
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Q) {
+        let unsure_marks = state.board.unsure_marks();
+        state.board.set_unsure_marks(!unsure_marks);
     }
 
     if app.keyboard.was_pressed(KeyCode::Return) {
-        state.stage = Stage::Paused;
+        state.stage = Stage::Paused(PauseMenu::new());
     }
 }
 
-fn transition_defeat(state: &mut State, triggered_pos: (usize, usize)) {
-    const EXPLOSION_RING_DELAY: u32 = 80;
+/// Carry out a player action on the board, along with everything following from it.
+fn act(state: &mut State, pos @ (x, y): (usize, usize), input: Input) {
+    state.recording.record(state.run_timer_milisec, input, pos);
+    undo::save(state);
+    state.hint = None;
 
-    let (width, height) = state.board().dims();
-    let mut explosions = vec![];
+    // kept for judging the click, should it turn out fatal
+    let before = (input == Input::Primary).then(|| state.board.clone());
 
-    for y in 0..height {
-        for x in 0..width {
-            let tile = state.board().tile(x, y);
+    let outcome = match input {
+        Input::Primary => state.board.handle_primary_action(x, y),
+        Input::Secondary => state.board.handle_secondary_action(x, y),
+    };
 
-            if !tile.is_mine() {
-                continue;
-            }
+    resolve(state, pos, outcome, before);
+}
 
-            let explosion = Explosion {
-                pos: (x, y),
-                delay: 0,
-            };
-            explosions.push(explosion);
+/// Everything following from an action on the board: the turn passing, the assists,
+/// the reveal wave, the sounds and the end of the game.
+///
+/// `before` is the board right before a primary action, to judge the action by should it be fatal.
+pub(super) fn resolve(
+    state: &mut State,
+    pos: (usize, usize),
+    mut outcome: ActionOutcome,
+    before: Option<Board>,
+) {
+    if state.mode == Mode::HotSeat {
+        end_turn(state, &outcome.revealed);
+    }
+
+    // kept for telling a mine set off by the assists from one the player set off
+    let mut before_assists = None;
+
+    // every tile of a hot-seat game is uncovered by one of the players
+    if state.assists.any() && state.mode != Mode::HotSeat {
+        state.assisted = true;
+        if outcome.terminal.is_none() {
+            before_assists = Some(state.board.clone());
+        }
+        let assisted = state.board.apply_assists(state.assists);
+        if !assisted.revealed.is_empty() || !assisted.marks.is_empty() {
+            state.recording.note_assists();
         }
+        outcome.extend(assisted);
+    }
+
+    if let Some(step_milisec) = state.reveal_speed.step_milisec() {
+        let steps = outcome
+            .revealed
+            .iter()
+            .map(|reveal| (reveal.pos, reveal.depth));
+        state
+            .animations
+            .start_wave(Effect::Reveal, steps, step_milisec);
     }
 
-    explosions.sort_by_key(|expl| distance(triggered_pos, expl.pos));
+    state.audio.react(&outcome);
+
+    match outcome.terminal {
+        Some(Terminal::Defeat { .. }) if survives(state, outcome.explosions()) => {
+            state.board.survive();
 
-    let rings = explosions
-        .iter_mut()
-        .group_by(|expl| distance(triggered_pos, expl.pos));
-    let mut current_delay = 0;
-    for (_, ring) in &rings {
-        for expl in ring {
-            expl.delay = current_delay;
+            // the explosion may have been all that stood between the player and victory
+            if state.board.is_victory() {
+                transition_cleared(state, pos);
+            }
         }
+        Some(Terminal::Defeat { triggered }) => {
+            let fatal_click = match before_assists {
+                Some(before) => Some(FatalClick::judge_assists(&before, triggered)),
+                None => before.map(|before| FatalClick::judge(&before, pos, triggered)),
+            };
+            transition_defeat(state, triggered, fatal_click);
+        }
+        Some(Terminal::Victory) => transition_cleared(state, pos),
+        None => (),
+    }
+}
 
-        current_delay += EXPLOSION_RING_DELAY;
+fn transition_defeat(
+    state: &mut State,
+    triggered_pos: (usize, usize),
+    fatal_click: Option<FatalClick>,
+) {
+    const EXPLOSION_RING_DELAY: u32 = 80;
+
+    let mines = mined_tiles(&state.board).collect();
+    let explosions = rings(&state.board, triggered_pos, mines);
+
+    // the first ring is the mine the action set off, already heard as part of its outcome
+    let last_ring = explosions.iter().map(|&(_, ring)| ring).max().unwrap_or(0);
+    for ring in 1..=last_ring {
+        state
+            .audio
+            .play_later(Sound::Explosion, ring as u32 * EXPLOSION_RING_DELAY);
     }
 
-    state.stage = Stage::Defeat(DefeatState {
-        explosions,
-        elapsed_milisec: 0,
+    state
+        .animations
+        .start_wave(Effect::Explosion, explosions, EXPLOSION_RING_DELAY);
+    state.stage = Stage::Defeat(PostMortem::new(&state.board, fatal_click));
+}
+
+/// The board is cleared, flags pop in over the mines left starting from `last_pos`,
+/// followed by a summary of the game.
+fn transition_victory(state: &mut State, last_pos: (usize, usize)) {
+    const CELEBRATION_RING_DELAY: u32 = 60;
+
+    let best_before = state.best_time();
+
+    let saved = match (state.puzzle, state.daily) {
+        // hints and undos make short work of a puzzle
+        (Some(_), _) if state.assisted => Ok(()),
+        (Some(idx), _) => state
+            .completions
+            .submit(&state.puzzles[idx], state.run_timer_milisec),
+        // the daily layout is known in advance after the first attempt
+        (None, Some(_)) => Ok(()),
+        // the time is shared between the players
+        (None, None) if state.mode == Mode::HotSeat => Ok(()),
+        (None, None) => state.leaderboard.submit(Record {
+            params: state.board.params(),
+            time_milisec: state.run_timer_milisec,
+            assisted: state.assisted,
+            practice: state.practice,
+            mode: state.mode,
+        }),
+    };
+
+    let board = &state.board;
+    let covered_mines = mined_tiles(board)
+        .filter(|&(x, y)| matches!(board.tile(x, y).cover(), Cover::Up(_)))
+        .collect();
+    let flags = rings(board, last_pos, covered_mines);
+
+    state
+        .animations
+        .start_wave(Effect::Celebration, flags, CELEBRATION_RING_DELAY);
+    state.stage = Stage::Victory(VictoryState {
+        time_milisec: state.run_timer_milisec,
+        three_bv: state.board.three_bv(),
+        clicks: state.recording.steps().len(),
+        personal_best: state.best_time() != best_before,
+        save_status: saved
+            .err()
+            .map(|err| format!("saving the time failed: {err}")),
     });
 }
 
-fn distance((from_x, from_y): (usize, usize), (p_x, p_y): (usize, usize)) -> usize {
-    let x_projection = usize::abs_diff(from_x, p_x);
-    let y_projection = usize::abs_diff(from_y, p_y);
+fn mined_tiles(board: &Board) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let (width, height) = board.dims();
+
+    (0..height)
+        .flat_map(move |y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| board.tile(x, y).is_mine())
+}
+
+/// Number the tiles by ring around `origin`, for animating them in a wave.
+///
+/// Every distinct distance is a ring of its own, however far apart they are.
+fn rings(
+    board: &Board,
+    origin: (usize, usize),
+    mut tiles: Vec<(usize, usize)>,
+) -> Vec<((usize, usize), usize)> {
+    let dims = board.dims();
+    let topology = board.params().topology;
+    let distance = |pos| topology.distance(dims, origin, pos);
+
+    tiles.sort_by_key(|&pos| distance(pos));
+
+    let rings = tiles.into_iter().group_by(|&pos| distance(pos));
+    rings
+        .into_iter()
+        .enumerate()
+        .flat_map(|(ring, (_, tiles))| tiles.map(move |pos| (pos, ring)))
+        .collect()
+}
+
+/// Whether the game goes on after the given number of explosions,
+/// taking the lives it costs.
+fn survives(state: &mut State, explosions: usize) -> bool {
+    match state.mode {
+        Mode::Lives(_) if explosions <= state.lives_left as usize => {
+            state.lives_left -= explosions as u8;
+            true
+        }
+        Mode::HotSeat => true,
+        Mode::Classic | Mode::Countdown { .. } | Mode::Lives(_) | Mode::Survival => false,
+    }
+}
+
+/// Every tile without a mine is uncovered, which only ends a [Mode::Survival] game
+/// once it goes on to a board that is not cleared.
+fn transition_cleared(state: &mut State, last_pos: (usize, usize)) {
+    match state.mode {
+        Mode::Survival => transition_next_board(state),
+        _ => transition_victory(state, last_pos),
+    }
+}
+
+/// The time of a [Mode::Countdown] game ran out,
+/// the mines go off starting from the middle of the board.
+fn transition_timeout(state: &mut State, limit_milisec: u32) {
+    let (width, height) = state.board().dims();
+
+    state.run_timer_milisec = limit_milisec;
+    transition_defeat(state, (width / 2, height / 2), None);
+}
+
+/// A board of a [Mode::Survival] game was cleared, on to the next one.
+fn transition_next_board(state: &mut State) {
+    state.boards_cleared += 1;
+    state.board.reset();
+    state.animations.clear();
+    state.recording.clear();
+    state.history.clear();
+    state.hint = None;
+}
+
+/// Score the tiles the active player uncovered, and pass the turn if they uncovered any.
+///
+/// Marking tiles never ends a turn, since the flags are shared.
+fn end_turn(state: &mut State, revealed: &[Reveal]) {
+    if revealed.is_empty() {
+        return;
+    }
+
+    let score: i32 = revealed
+        .iter()
+        .map(|reveal| match reveal.object {
+            Object::Mine(mines) => -MINE_PENALTY * mines as i32,
+            Object::Hint(_) | Object::Blank => 1,
+        })
+        .sum();
 
-    std::cmp::max(x_projection, y_projection)
+    state.scores[state.active_player] += score;
+    state.active_player = (state.active_player + 1) % PLAYERS;
 }
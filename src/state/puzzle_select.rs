@@ -0,0 +1,31 @@
+use notan::prelude::*;
+
+use crate::minefield::EXPERT;
+use crate::state::{Stage, State};
+
+pub fn update(app: &mut App, state: &mut State) {
+    state.hover = None;
+
+    let Stage::PuzzleSelect(selected) = &mut state.stage else {
+        return;
+    };
+
+    let last = state.puzzles.len().saturating_sub(1);
+
+    if app.keyboard.was_pressed(KeyCode::Up) || app.keyboard.was_pressed(KeyCode::W) {
+        *selected = selected.saturating_sub(1);
+    }
+    if app.keyboard.was_pressed(KeyCode::Down) || app.keyboard.was_pressed(KeyCode::S) {
+        *selected = usize::min(*selected + 1, last);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Return) && !state.puzzles.is_empty() {
+        let selected = *selected;
+        state.start_puzzle(app, selected);
+    } else if app.keyboard.was_pressed(KeyCode::Escape) {
+        match state.puzzle {
+            Some(_) => state.set_params(app, EXPERT),
+            None => state.stage = Stage::Playing,
+        }
+    }
+}
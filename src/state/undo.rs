@@ -0,0 +1,103 @@
+use crate::minefield::Board;
+use crate::state::{Mode, Stage, State};
+
+/// The game as it was right before an action, to go back to.
+#[derive(Debug)]
+pub struct Snapshot {
+    board: Board,
+    lives_left: u8,
+}
+
+impl Snapshot {
+    fn new(board: &Board, lives_left: u8) -> Self {
+        Self {
+            board: board.clone(),
+            lives_left,
+        }
+    }
+
+    /// Put the saved board back in place of `board`, returning the lives left back then.
+    ///
+    /// Rule options changed since are kept.
+    fn restore(self, board: &mut Board) -> u8 {
+        let unsure_marks = board.unsure_marks();
+        *board = self.board;
+        board.set_unsure_marks(unsure_marks);
+
+        self.lives_left
+    }
+}
+
+/// Remember the game as it is, before acting on it, if it may be gone back to.
+pub fn save(state: &mut State) {
+    if !is_allowed(state) {
+        return;
+    }
+
+    let snapshot = Snapshot::new(&state.board, state.lives_left);
+    state.history.push(snapshot);
+}
+
+/// Whether the last action can be taken back.
+///
+/// A game lost to the clock is final.
+pub fn can_undo(state: &State) -> bool {
+    let undoable_stage = match &state.stage {
+        Stage::Playing => true,
+        Stage::Defeat(post_mortem) => post_mortem.fatal_click.is_some(),
+        _ => false,
+    };
+
+    is_allowed(state) && undoable_stage && !state.history.is_empty()
+}
+
+/// Whether the game allows undos at all, hot-seat turns and the daily challenge being final.
+fn is_allowed(state: &State) -> bool {
+    state.mode != Mode::HotSeat && state.daily.is_none()
+}
+
+/// Take back the last action, which counts as an assist.
+pub fn undo(state: &mut State) {
+    let Some(snapshot) = state.history.pop() else {
+        return;
+    };
+
+    state.lives_left = snapshot.restore(&mut state.board);
+
+    state.stage = Stage::Playing;
+    state.assisted = true;
+    state.hint = None;
+    state.recording.pop();
+    state.animations.clear();
+    state.audio.cancel_scheduled();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::{Cover, Params, BEGINNER};
+
+    #[test]
+    fn restore_takes_the_board_back_and_keeps_the_rules() {
+        let mut board = Board::new(Params {
+            width: 4,
+            height: 1,
+            mines: 1,
+            ..BEGINNER
+        });
+        board.place_layout(&[(2, 0)]);
+        board.handle_primary_action(0, 0);
+
+        let snapshot = Snapshot::new(&board, 2);
+        let unsure_marks = !board.unsure_marks();
+        board.set_unsure_marks(unsure_marks);
+        board.handle_primary_action(2, 0);
+        assert!(board.is_defeat());
+
+        assert_eq!(snapshot.restore(&mut board), 2);
+        assert!(!board.is_defeat());
+        assert!(matches!(board.tile(2, 0).cover(), Cover::Up(_)));
+        assert!(matches!(board.tile(0, 0).cover(), Cover::Down));
+        assert_eq!(board.unsure_marks(), unsure_marks);
+    }
+}
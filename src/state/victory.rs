@@ -1,13 +1,77 @@
 use notan::prelude::*;
 
+use crate::minefield::{BEGINNER, EXPERT, INTERMEDIATE};
+use crate::replay::Replay;
 use crate::state::{Stage, State};
 
+/// How the won game went, for the summary shown once the celebration is over.
+#[derive(Debug)]
+pub struct VictoryState {
+    pub time_milisec: u32,
+
+    /// Minimum number of clicks the board needed, see [Board::three_bv](crate::minefield::Board::three_bv).
+    pub three_bv: usize,
+
+    /// Clicks actually made, of either mouse button.
+    pub clicks: usize,
+
+    /// The time beats the best one on the board's leaderboard.
+    pub personal_best: bool,
+
+    /// Outcome of the last attempt at saving the replay,
+    /// or why the time could not be kept for later sessions.
+    pub save_status: Option<String>,
+}
+
+impl VictoryState {
+    pub fn three_bv_per_second(&self) -> f32 {
+        self.three_bv as f32 / (self.time_milisec.max(1) as f32 / 1000.)
+    }
+
+    /// Share of the clicks that were needed at all, as a percentage.
+    pub fn efficiency(&self) -> f32 {
+        100. * self.three_bv as f32 / self.clicks.max(1) as f32
+    }
+}
+
 pub fn update(app: &mut App, state: &mut State) {
     state.hover = None;
 
     if app.keyboard.was_pressed(KeyCode::Space) {
-        state.stage = Stage::Playing;
-        state.run_timer_milisec = 0;
-        state.board.reset();
+        state.restart();
+    }
+
+    let difficulties = [
+        (KeyCode::Key1, BEGINNER),
+        (KeyCode::Key2, INTERMEDIATE),
+        (KeyCode::Key3, EXPERT),
+    ];
+
+    for (key, params) in difficulties {
+        if app.keyboard.was_pressed(key) {
+            state.set_params(app, params);
+            return;
+        }
+    }
+
+    if app.keyboard.was_pressed(KeyCode::S) {
+        let replay = Replay::new(&state.board, &state.recording, state.mode, state.assisted);
+        let status = match replay.map(|replay| replay.save()) {
+            Some(Ok(())) => "replay saved".to_owned(),
+            Some(Err(err)) => format!("saving failed: {err}"),
+            None => "no replay, the assists' moves were not recorded".to_owned(),
+        };
+
+        if let Stage::Victory(victory) = &mut state.stage {
+            victory.save_status = Some(status);
+        }
+    }
+
+    if app.keyboard.was_pressed(KeyCode::X) {
+        state.share_daily(app);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::P) {
+        state.stage = Stage::PuzzleSelect(state.puzzle.unwrap_or(0));
     }
 }
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+use crate::minefield::{FirstClick, Neighborhood, Params, Topology};
+use crate::puzzle::{Pack, Puzzle};
+use crate::state::Mode;
+
+/// Every won game, one [Record] per line, relative to the working directory.
+pub const LEADERBOARD_PATH: &str = "leaderboard.txt";
+
+/// Every solved puzzle, one completion per line, relative to the working directory.
+pub const COMPLETIONS_PATH: &str = "puzzle_completions.txt";
+
+/// A won game, as remembered by the leaderboard.
+#[derive(Debug, Clone, Copy)]
+pub struct Record {
+    pub params: Params,
+    pub time_milisec: u32,
+
+    /// Any of the [Assists](crate::minefield::Assists) was active during the game,
+    /// or a hint or an undo was used.
+    pub assisted: bool,
+
+    /// The game was a retry of a board already played, see [Board::retry](crate::minefield::Board::retry).
+    pub practice: bool,
+
+    pub mode: Mode,
+}
+
+#[derive(Debug, Default)]
+pub struct Leaderboard {
+    records: Vec<Record>,
+}
+
+impl Record {
+    /// The record written as by [Record::line], `None` if it is malformed.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split(';').map(str::trim);
+        let mut next = || fields.next();
+
+        let time_milisec = next()?.parse().ok()?;
+        let (width, height) = next()?.split_once('x')?;
+        let (mines, mines_per_tile) = next()?.split_once('/')?;
+        let params = Params {
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+            mines: mines.parse().ok()?,
+            mines_per_tile: mines_per_tile.parse().ok()?,
+            topology: Topology::parse(next()?)?,
+            neighborhood: Neighborhood::parse(next()?)?,
+            first_click: FirstClick::parse(next()?)?,
+        };
+        let mode = Mode::parse(next()?)?;
+        let yes_no = |field: &str| match field {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        };
+        let assisted = yes_no(next()?)?;
+        let practice = yes_no(next()?)?;
+
+        Some(Self {
+            params,
+            time_milisec,
+            assisted,
+            practice,
+            mode,
+        })
+    }
+
+    /// The record on a single line, its fields separated by semicolons:
+    ///
+    /// ```text
+    /// 48213;30x16;99/1;bounded;moore;safe-area;classic;no;no
+    /// ```
+    ///
+    /// That is the time, the size, the mines and how many a tile holds at most,
+    /// the topology, the neighborhood, the first-click policy, the mode,
+    /// whether the game was assisted and whether it was practice.
+    fn line(&self) -> String {
+        let params = self.params;
+        let yes_no = |yes| if yes { "yes" } else { "no" };
+
+        format!(
+            "{};{}x{};{}/{};{};{};{};{};{};{}",
+            self.time_milisec,
+            params.width,
+            params.height,
+            params.mines,
+            params.mines_per_tile,
+            params.topology,
+            params.neighborhood,
+            params.first_click,
+            self.mode,
+            yes_no(self.assisted),
+            yes_no(self.practice),
+        )
+    }
+}
+
+impl Leaderboard {
+    /// The records kept at [LEADERBOARD_PATH], none if there is no such file.
+    ///
+    /// Malformed lines are skipped, so that a hand-edited file never keeps the game from starting.
+    pub fn load() -> Self {
+        Self::parse(&fs::read_to_string(LEADERBOARD_PATH).unwrap_or_default())
+    }
+
+    fn parse(source: &str) -> Self {
+        Self {
+            records: source.lines().filter_map(Record::parse).collect(),
+        }
+    }
+
+    /// Remember a won game, in this session even if it cannot be written to [LEADERBOARD_PATH].
+    pub fn submit(&mut self, record: Record) -> io::Result<()> {
+        self.records.push(record);
+        append_line(LEADERBOARD_PATH, &record.line())
+    }
+
+    /// Best time for the given difficulty, counting only games played without assists,
+    /// without surviving any explosion and on a board seen for the first time.
+    pub fn pure_best(&self, params: Params) -> Option<&Record> {
+        self.records
+            .iter()
+            .filter(|record| record.params == params && !record.assisted && !record.practice)
+            .filter(|record| !matches!(record.mode, Mode::Lives(_)))
+            .min_by_key(|record| record.time_milisec)
+    }
+}
+
+/// Which puzzles were solved, and how fast.
+///
+/// Puzzles are told apart by their pack and title, which stay the same
+/// when the custom pack grows, unlike their place in the list of loaded puzzles.
+#[derive(Debug, Default)]
+pub struct Completions {
+    best_milisec: HashMap<(Pack, String), u32>,
+}
+
+impl Completions {
+    /// The completions kept at [COMPLETIONS_PATH], none if there is no such file.
+    ///
+    /// Every line is a pack name, a time and a title, separated by semicolons:
+    ///
+    /// ```text
+    /// builtin;5210;First steps
+    /// ```
+    ///
+    /// Malformed lines are skipped, so that a hand-edited file never keeps the game from starting.
+    pub fn load() -> Self {
+        Self::parse(&fs::read_to_string(COMPLETIONS_PATH).unwrap_or_default())
+    }
+
+    fn parse(source: &str) -> Self {
+        let mut completions = Self::default();
+
+        for (pack, time_milisec, title) in source.lines().filter_map(parse_completion) {
+            completions.remember((pack, title.to_owned()), time_milisec);
+        }
+
+        completions
+    }
+
+    /// Remember a solved puzzle, in this session even if it cannot be written to [COMPLETIONS_PATH].
+    pub fn submit(&mut self, puzzle: &Puzzle, time_milisec: u32) -> io::Result<()> {
+        self.remember((puzzle.pack, puzzle.title.clone()), time_milisec);
+
+        let line = format!("{};{time_milisec};{}", puzzle.pack.name(), puzzle.title);
+        append_line(COMPLETIONS_PATH, &line)
+    }
+
+    /// Best time for the given puzzle, if it was ever solved.
+    pub fn best(&self, puzzle: &Puzzle) -> Option<u32> {
+        self.best_milisec
+            .get(&(puzzle.pack, puzzle.title.clone()))
+            .copied()
+    }
+
+    fn remember(&mut self, key: (Pack, String), time_milisec: u32) {
+        self.best_milisec
+            .entry(key)
+            .and_modify(|best| *best = u32::min(*best, time_milisec))
+            .or_insert(time_milisec);
+    }
+}
+
+/// A line of [COMPLETIONS_PATH] as its pack, time and title, `None` if it is malformed.
+fn parse_completion(line: &str) -> Option<(Pack, u32, &str)> {
+    // the title goes last, as nothing keeps it from holding a semicolon
+    let mut fields = line.splitn(3, ';');
+    let pack = Pack::parse(fields.next()?)?;
+    let time_milisec = fields.next()?.parse().ok()?;
+
+    Some((pack, time_milisec, fields.next()?))
+}
+
+/// Append a line to the file at `path`, creating it if needed.
+fn append_line(path: &str, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minefield::EXPERT;
+
+    #[test]
+    fn records_read_back_what_they_write() {
+        let record = Record {
+            params: Params {
+                topology: Topology::Layered(3),
+                neighborhood: Neighborhood::Knight,
+                first_click: FirstClick::Opening(20),
+                mines_per_tile: 2,
+                ..EXPERT
+            },
+            time_milisec: 48_213,
+            assisted: true,
+            practice: false,
+            mode: Mode::Countdown {
+                limit_milisec: 60_000,
+            },
+        };
+
+        let line = record.line();
+        assert_eq!(
+            line,
+            "48213;30x16;99/2;layered 3;knight;opening 20;countdown 60000;yes;no"
+        );
+
+        let parsed = Record::parse(&line).unwrap();
+        assert_eq!(parsed.params, record.params);
+        assert_eq!(parsed.time_milisec, record.time_milisec);
+        assert_eq!((parsed.assisted, parsed.practice), (true, false));
+        assert_eq!(parsed.mode, record.mode);
+    }
+
+    #[test]
+    fn pure_best_skips_assisted_practice_and_lives_games() {
+        let leaderboard = Leaderboard::parse(
+            "\
+50000;30x16;99/1;bounded;moore;safe-area;classic;no;no
+10000;30x16;99/1;bounded;moore;safe-area;classic;yes;no
+20000;30x16;99/1;bounded;moore;safe-area;classic;no;yes
+30000;30x16;99/1;bounded;moore;safe-area;lives 3;no;no
+40000;30x16;99/1;bounded;moore;safe-area;survival;no;no
+not a record
+",
+        );
+
+        let best = leaderboard.pure_best(EXPERT).unwrap();
+        assert_eq!(best.time_milisec, 40_000);
+        assert_eq!(best.mode, Mode::Survival);
+    }
+
+    fn puzzle(title: &str, pack: Pack) -> Puzzle {
+        let mut puzzle = Puzzle::new(title.to_owned(), 1, 2, 2);
+        puzzle.pack = pack;
+        puzzle
+    }
+
+    #[test]
+    fn completions_keep_the_best_time_by_pack_and_title() {
+        let completions = Completions::parse(
+            "\
+builtin;5210;First steps
+builtin;4100;First steps
+custom;900;First steps
+custom;700;Semi;colon
+custom;fast;Broken
+",
+        );
+
+        let best = |title, pack| completions.best(&puzzle(title, pack));
+        assert_eq!(best("First steps", Pack::Builtin), Some(4100));
+        assert_eq!(best("First steps", Pack::Custom), Some(900));
+        assert_eq!(best("Semi;colon", Pack::Custom), Some(700));
+        assert_eq!(best("Broken", Pack::Custom), None);
+    }
+}